@@ -0,0 +1,206 @@
+//! Compares `ClosedGenVec` against its previous layout, where an `IndexAllocator` kept a `VecDeque` of free
+//! slots beside its slot states and the values were stored in an `ExposedGenVec` with a second generation each
+//!
+//! Run with `cargo bench --bench layout`
+
+use std::{collections::VecDeque, hint::black_box, mem, time::Instant};
+use gen_vec::{Index, closed::ClosedGenVec, exposed::IndexAllocator};
+
+const ITEMS: usize = 100_000;
+const ROUNDS: usize = 20;
+
+/// The previous `ClosedGenVec` layout, reduced to what the benchmarks exercise
+struct PreviousLayout<T>
+{
+    free_indices: VecDeque<usize>,
+    active_indices: Vec<(bool, usize)>,
+    items: Vec<Option<(usize, T)>>
+}
+
+impl<T> PreviousLayout<T>
+{
+    fn new() -> PreviousLayout<T>
+    {
+        PreviousLayout { free_indices: VecDeque::new(), active_indices: Vec::new(), items: Vec::new() }
+    }
+
+    fn insert(&mut self, value: T) -> Index
+    {
+        let (slot, generation) = match self.free_indices.pop_front()
+        {
+            Some(slot) =>
+                {
+                    let (is_free, generation) = &mut self.active_indices[slot];
+                    *is_free = false;
+                    *generation += 1;
+                    (slot, *generation)
+                },
+            _ =>
+                {
+                    self.active_indices.push((false, 0));
+                    (self.active_indices.len() - 1, 0)
+                }
+        };
+        if self.items.len() <= slot
+        {
+            self.items.resize_with(slot + 1, | | None);
+        }
+        self.items[slot] = Some((generation, value));
+        Index::from((slot, generation))
+    }
+
+    fn get(&self, index: Index) -> Option<&T>
+    {
+        let (slot, generation) = index.into();
+        match self.items.get(slot)
+        {
+            Some(Some((current, value))) if *current == generation => Some(value),
+            _ => None
+        }
+    }
+
+    fn remove(&mut self, index: Index) -> Option<T>
+    {
+        let (slot, generation) = index.into();
+        match self.active_indices.get_mut(slot)
+        {
+            Some((is_free, current)) if !*is_free && *current == generation =>
+                {
+                    *is_free = true;
+                    self.free_indices.push_back(slot);
+                    self.items[slot].take().map(|(_, value)| value)
+                },
+            _ => None
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (Index, &T)>
+    {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, item)| item.as_ref().map(|(generation, value)| (Index::from((slot, *generation)), value)))
+    }
+
+    fn heap_bytes(&self) -> usize
+    {
+        self.free_indices.capacity() * mem::size_of::<usize>()
+            + self.active_indices.capacity() * mem::size_of::<(bool, usize)>()
+            + self.items.capacity() * mem::size_of::<Option<(usize, T)>>()
+    }
+}
+
+/// Operations shared by both layouts
+trait Layout
+{
+    fn insert(&mut self, value: u32) -> Index;
+    fn get(&self, index: Index) -> Option<&u32>;
+    fn remove(&mut self, index: Index) -> Option<u32>;
+    fn sum(&self) -> u64;
+    fn heap_bytes(&self) -> usize;
+}
+
+impl Layout for ClosedGenVec<u32>
+{
+    fn insert(&mut self, value: u32) -> Index { ClosedGenVec::insert(self, value) }
+    fn get(&self, index: Index) -> Option<&u32> { ClosedGenVec::get(self, index) }
+    fn remove(&mut self, index: Index) -> Option<u32> { ClosedGenVec::remove(self, index) }
+    fn sum(&self) -> u64 { self.iter().map(|(_, value)| u64::from(*value)).sum() }
+
+    fn heap_bytes(&self) -> usize
+    {
+        let stats = self.stats();
+        stats.items_bytes + stats.active_indices_bytes + stats.free_indices_bytes
+    }
+}
+
+impl Layout for PreviousLayout<u32>
+{
+    fn insert(&mut self, value: u32) -> Index { PreviousLayout::insert(self, value) }
+    fn get(&self, index: Index) -> Option<&u32> { PreviousLayout::get(self, index) }
+    fn remove(&mut self, index: Index) -> Option<u32> { PreviousLayout::remove(self, index) }
+    fn sum(&self) -> u64 { self.iter().map(|(_, value)| u64::from(*value)).sum() }
+    fn heap_bytes(&self) -> usize { PreviousLayout::heap_bytes(self) }
+}
+
+/// Average nanoseconds per item of running `f` over `ROUNDS` fresh layouts
+fn time<L: Layout, F: FnMut(&mut L, &mut Vec<Index>)>(new: fn() -> L, mut f: F) -> f64
+{
+    let mut elapsed = 0.0;
+    for _ in 0..ROUNDS
+    {
+        let mut layout = new();
+        let mut indices: Vec<Index> = (0..ITEMS as u32).map(|value| layout.insert(value)).collect();
+        let start = Instant::now();
+        f(&mut layout, &mut indices);
+        elapsed += start.elapsed().as_nanos() as f64;
+        black_box(&layout);
+    }
+    elapsed / (ROUNDS * ITEMS) as f64
+}
+
+fn run<L: Layout>(name: &str, new: fn() -> L)
+{
+    let insert = time(new, |layout, _| for value in 0..ITEMS as u32 { black_box(layout.insert(value)); });
+    let get = time(new, |layout, indices| for index in indices.iter() { black_box(layout.get(*index)); });
+    let churn = time(new, |layout, indices|
+        {
+            for index in indices.iter_mut().step_by(2)
+            {
+                layout.remove(*index);
+                *index = layout.insert(0);
+            }
+        });
+    let iter = time(new, |layout, indices|
+        {
+            for index in indices.iter().step_by(3)
+            {
+                layout.remove(*index);
+            }
+            black_box(layout.sum());
+        });
+
+    let mut layout = new();
+    let indices: Vec<Index> = (0..ITEMS as u32).map(|value| layout.insert(value)).collect();
+    for index in indices.iter().step_by(2)
+    {
+        layout.remove(*index);
+    }
+
+    println!("{:<16} insert {:>6.2} ns  get {:>6.2} ns  churn {:>6.2} ns  remove + iter {:>6.2} ns  {:>8} heap bytes",
+             name, insert, get, churn, iter, layout.heap_bytes());
+}
+
+fn index_allocator()
+{
+    let mut elapsed = 0.0;
+    for _ in 0..ROUNDS
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..ITEMS).map(|_| allocator.allocate()).collect();
+        for index in &indices
+        {
+            allocator.deallocate(*index);
+        }
+
+        // Pulls every other slot out of the middle of the free list
+        let start = Instant::now();
+        for index in indices.iter().step_by(2)
+        {
+            let (slot, generation) = (*index).into();
+            black_box(allocator.allocate_at(Index::from((slot, generation + 1)))).ok();
+        }
+        elapsed += start.elapsed().as_nanos() as f64;
+        black_box(&allocator);
+    }
+
+    println!("{:<16} allocate_at from the free list {:>6.2} ns", "IndexAllocator", elapsed / (ROUNDS * ITEMS / 2) as f64);
+}
+
+fn main()
+{
+    println!("{} u32 items, averaged over {} rounds, per item", ITEMS, ROUNDS);
+    run("previous layout", PreviousLayout::<u32>::new);
+    run("ClosedGenVec", ClosedGenVec::<u32>::new);
+    index_allocator();
+}
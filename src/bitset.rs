@@ -0,0 +1,547 @@
+use std::mem;
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+const BITS: usize = u64::BITS as usize;
+
+/// Two level bitset of the occupied slots of a generational vec or `IndexAllocator`
+///
+/// Each bit of `words` marks an occupied slot and each bit of `summary` marks a non-zero word,
+/// so walking the set slots skips 64 empty words at a time
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::OccupancyBits;
+///
+/// let mut bits = OccupancyBits::new();
+/// bits.insert(3);
+/// bits.insert(4000);
+/// assert!(bits.contains(3));
+/// assert_eq!(bits.iter().collect::<Vec<usize>>(), vec![3, 4000]);
+/// ```
+#[derive(Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OccupancyBits
+{
+    words: Vec<u64>,
+    summary: Vec<u64>,
+    /// First slot the bitset can hold. Bits are stored relative to it so slots far from 0 stay cheap
+    offset: usize
+}
+
+impl OccupancyBits
+{
+    /// Returns an empty `OccupancyBits`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let bits = OccupancyBits::new();
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn new() -> OccupancyBits
+    {
+        OccupancyBits::default()
+    }
+
+    /// Returns an empty `OccupancyBits` for slots from `offset` onwards
+    pub(crate) fn with_offset(offset: usize) -> OccupancyBits
+    {
+        OccupancyBits { offset, ..OccupancyBits::default() }
+    }
+
+    /// Marks `slot` as occupied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(70);
+    /// assert!(bits.contains(70));
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, slot: usize)
+    {
+        let slot = match slot.checked_sub(self.offset)
+        {
+            Some(slot) => slot,
+            _ => panic!("Slot {} is below the first slot {} of the bitset", slot, self.offset)
+        };
+        let word = slot / BITS;
+        if self.words.len() <= word
+        {
+            self.words.resize(word + 1, 0);
+            self.summary.resize(word / BITS + 1, 0);
+        }
+        self.words[word] |= 1 << (slot % BITS);
+        self.summary[word / BITS] |= 1 << (word % BITS);
+    }
+
+    /// Marks `slot` as vacant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(70);
+    /// bits.remove(70);
+    /// assert!(!bits.contains(70));
+    /// ```
+    #[inline]
+    pub fn remove(&mut self, slot: usize)
+    {
+        let slot = match slot.checked_sub(self.offset)
+        {
+            Some(slot) => slot,
+            _ => return
+        };
+        let word = slot / BITS;
+        if let Some(bits) = self.words.get_mut(word)
+        {
+            *bits &= !(1 << (slot % BITS));
+            if *bits == 0
+            {
+                self.summary[word / BITS] &= !(1 << (word % BITS));
+            }
+        }
+    }
+
+    /// Returns `true` if `slot` is occupied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// assert!(!bits.contains(2));
+    /// bits.insert(2);
+    /// assert!(bits.contains(2));
+    /// ```
+    #[inline]
+    pub fn contains(&self, slot: usize) -> bool
+    {
+        let slot = match slot.checked_sub(self.offset)
+        {
+            Some(slot) => slot,
+            _ => return false
+        };
+        match self.words.get(slot / BITS)
+        {
+            Some(bits) => bits & (1 << (slot % BITS)) != 0,
+            _ => false
+        }
+    }
+
+    /// Number of occupied slots
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(0);
+    /// bits.insert(100);
+    /// assert_eq!(bits.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.words.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if no slot is occupied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(5);
+    /// bits.remove(5);
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.summary.iter().all(|bits| *bits == 0)
+    }
+
+    /// Marks every slot as vacant
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(5);
+    /// bits.clear();
+    /// assert!(bits.is_empty());
+    /// ```
+    pub fn clear(&mut self)
+    {
+        self.words.clear();
+        self.summary.clear();
+    }
+
+    /// Returns the first occupied slot at or after `from`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(5);
+    /// bits.insert(9000);
+    /// assert_eq!(bits.next_occupied(0), Some(5));
+    /// assert_eq!(bits.next_occupied(6), Some(9000));
+    /// assert_eq!(bits.next_occupied(9001), None);
+    /// ```
+    #[inline]
+    pub fn next_occupied(&self, from: usize) -> Option<usize>
+    {
+        self.next_relative(from.saturating_sub(self.offset)).map(|slot| slot + self.offset)
+    }
+
+    /// First occupied slot at or after `from`, both counted from `offset`
+    #[inline]
+    fn next_relative(&self, from: usize) -> Option<usize>
+    {
+        let word = from / BITS;
+        let bits = self.words.get(word)? & (!0 << (from % BITS));
+        if bits != 0
+        {
+            return Some(word * BITS + bits.trailing_zeros() as usize);
+        }
+
+        // Find the next non-zero word through the summary
+        let next = word + 1;
+        let mut summary_word = next / BITS;
+        let mut summary_bits = self.summary.get(summary_word)? & (!0 << (next % BITS));
+        loop
+        {
+            if summary_bits != 0
+            {
+                let word = summary_word * BITS + summary_bits.trailing_zeros() as usize;
+                return Some(word * BITS + self.words[word].trailing_zeros() as usize);
+            }
+            summary_word += 1;
+            summary_bits = *self.summary.get(summary_word)?;
+        }
+    }
+
+    /// Returns the last occupied slot before `before`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(5);
+    /// bits.insert(9000);
+    /// assert_eq!(bits.prev_occupied(usize::MAX), Some(9000));
+    /// assert_eq!(bits.prev_occupied(9000), Some(5));
+    /// assert_eq!(bits.prev_occupied(5), None);
+    /// ```
+    #[inline]
+    pub fn prev_occupied(&self, before: usize) -> Option<usize>
+    {
+        self.prev_relative(before.checked_sub(self.offset)?).map(|slot| slot + self.offset)
+    }
+
+    /// Last occupied slot before `before`, both counted from `offset`
+    #[inline]
+    fn prev_relative(&self, before: usize) -> Option<usize>
+    {
+        let last = before.checked_sub(1)?;
+        let word = last / BITS;
+        if let Some(bits) = self.words.get(word)
+        {
+            let bits = bits & (!0 >> (BITS - 1 - last % BITS));
+            if bits != 0
+            {
+                return Some(word * BITS + highest_bit(bits));
+            }
+        }
+
+        // Find the previous non-zero word through the summary
+        let last_word = word.min(self.words.len()).checked_sub(1)?;
+        let mut summary_word = last_word / BITS;
+        let mut summary_bits = self.summary.get(summary_word)? & (!0 >> (BITS - 1 - last_word % BITS));
+        loop
+        {
+            if summary_bits != 0
+            {
+                let word = summary_word * BITS + highest_bit(summary_bits);
+                return Some(word * BITS + highest_bit(self.words[word]));
+            }
+            summary_word = summary_word.checked_sub(1)?;
+            summary_bits = self.summary[summary_word];
+        }
+    }
+
+    /// Returns an iterator over the occupied slots in ascending order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(64);
+    /// bits.insert(1);
+    /// assert_eq!(bits.iter().collect::<Vec<usize>>(), vec![1, 64]);
+    /// ```
+    pub fn iter(&self) -> Ones<'_>
+    {
+        Ones { bits: self, word: 0, current: self.words.first().copied().unwrap_or(0) }
+    }
+
+    /// Keeps only the slots that are also occupied in `other`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(1);
+    /// bits.insert(2);
+    ///
+    /// let mut other = OccupancyBits::new();
+    /// other.insert(2);
+    /// other.insert(3);
+    ///
+    /// bits.intersect_with(&other);
+    /// assert_eq!(bits.iter().collect::<Vec<usize>>(), vec![2]);
+    /// ```
+    pub fn intersect_with(&mut self, other: &OccupancyBits)
+    {
+        if self.offset != other.offset
+        {
+            let vacant: Vec<usize> = self.iter().filter(|slot| !other.contains(*slot)).collect();
+            for slot in vacant
+            {
+                self.remove(slot);
+            }
+            return;
+        }
+
+        self.words.truncate(other.words.len());
+        self.summary.truncate(other.summary.len());
+        for (bits, other) in self.words.iter_mut().zip(&other.words)
+        {
+            *bits &= other;
+        }
+        self.rebuild_summary();
+    }
+
+    /// Removes every slot at or after `at`, returning them in a new `OccupancyBits`
+    pub(crate) fn split_off(&mut self, at: usize) -> OccupancyBits
+    {
+        let mut other = OccupancyBits::with_offset(at.max(self.offset));
+        while let Some(slot) = self.next_occupied(at)
+        {
+            self.remove(slot);
+            other.insert(slot);
+        }
+        other
+    }
+
+    /// Approximate heap bytes used by the bitset
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::OccupancyBits;
+    ///
+    /// let mut bits = OccupancyBits::new();
+    /// bits.insert(0);
+    /// assert!(bits.heap_bytes() >= 16);
+    /// ```
+    pub fn heap_bytes(&self) -> usize
+    {
+        (self.words.capacity() + self.summary.capacity()) * mem::size_of::<u64>()
+    }
+
+    /// Recomputes `summary` from `words`
+    fn rebuild_summary(&mut self)
+    {
+        for bits in self.summary.iter_mut()
+        {
+            *bits = 0;
+        }
+        for (word, bits) in self.words.iter().enumerate()
+        {
+            if *bits != 0
+            {
+                self.summary[word / BITS] |= 1 << (word % BITS);
+            }
+        }
+    }
+}
+
+/// Position of the highest set bit of non-zero `bits`
+fn highest_bit(bits: u64) -> usize
+{
+    BITS - 1 - bits.leading_zeros() as usize
+}
+
+/// Iterator over the occupied slots of an `OccupancyBits`
+#[derive(Debug)]
+pub struct Ones<'a>
+{
+    bits: &'a OccupancyBits,
+    /// Word the remaining bits of `current` come from
+    word: usize,
+    /// Occupied slots of `word` that haven't been visited yet
+    current: u64
+}
+
+impl<'a> Iterator for Ones<'a>
+{
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize>
+    {
+        while self.current == 0
+        {
+            let slot = self.bits.next_relative((self.word + 1) * BITS)?;
+            self.word = slot / BITS;
+            self.current = self.bits.words[self.word];
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        self.current &= self.current - 1;
+        Some(self.bits.offset + self.word * BITS + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a OccupancyBits
+{
+    type Item = usize;
+    type IntoIter = Ones<'a>;
+
+    fn into_iter(self) -> Ones<'a>
+    {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod bitset_tests
+{
+    use crate::OccupancyBits;
+
+    #[test]
+    fn next_occupied_skips_empty_words()
+    {
+        let mut bits = OccupancyBits::new();
+        let slots = [0, 63, 64, 4095, 4096, 262_143, 262_144, 1_000_000];
+        for slot in slots
+        {
+            bits.insert(slot);
+        }
+        assert_eq!(bits.iter().collect::<Vec<usize>>(), slots);
+        assert_eq!(bits.len(), slots.len());
+
+        for slot in slots
+        {
+            bits.remove(slot);
+        }
+        assert!(bits.is_empty());
+        assert_eq!(bits.next_occupied(0), None);
+    }
+
+    #[test]
+    fn prev_occupied_skips_empty_words()
+    {
+        let mut bits = OccupancyBits::new();
+        let slots = [0, 63, 64, 4095, 4096, 262_143, 262_144, 1_000_000];
+        for slot in slots
+        {
+            bits.insert(slot);
+        }
+
+        let mut reversed = Vec::new();
+        let mut before = usize::MAX;
+        while let Some(slot) = bits.prev_occupied(before)
+        {
+            reversed.push(slot);
+            before = slot;
+        }
+        reversed.reverse();
+        assert_eq!(reversed, slots);
+
+        assert_eq!(bits.prev_occupied(0), None);
+        assert_eq!(bits.prev_occupied(63), Some(0));
+        assert_eq!(bits.prev_occupied(4000), Some(64));
+        assert_eq!(bits.prev_occupied(999_999), Some(262_144));
+
+        bits.remove(0);
+        assert_eq!(bits.prev_occupied(63), None);
+
+        let mut bits = OccupancyBits::with_offset(1 << 40);
+        bits.insert((1 << 40) + 3);
+        assert_eq!(bits.prev_occupied(3), None);
+        assert_eq!(bits.prev_occupied((1 << 40) + 4), Some((1 << 40) + 3));
+    }
+
+    #[test]
+    fn intersect_and_split()
+    {
+        let mut bits = OccupancyBits::new();
+        let mut other = OccupancyBits::new();
+        for slot in 0..200
+        {
+            bits.insert(slot);
+            if slot % 3 == 0 && slot < 150
+            {
+                other.insert(slot);
+            }
+        }
+
+        bits.intersect_with(&other);
+        assert_eq!(bits.iter().collect::<Vec<usize>>(), (0..150).step_by(3).collect::<Vec<usize>>());
+
+        let split = bits.split_off(100);
+        assert_eq!(bits.iter().last(), Some(99));
+        assert_eq!(split.iter().next(), Some(102));
+        assert!(!bits.contains(102));
+
+        // Bitsets with different offsets still intersect slot by slot
+        let mut other = OccupancyBits::new();
+        other.insert(105);
+        other.insert(120);
+        let mut split = split;
+        split.intersect_with(&other);
+        assert_eq!(split.iter().collect::<Vec<usize>>(), vec![105, 120]);
+    }
+
+    #[test]
+    fn offset()
+    {
+        let mut bits = OccupancyBits::with_offset(1 << 40);
+        bits.insert((1 << 40) + 3);
+        bits.insert((1 << 40) + 200);
+        assert!(bits.heap_bytes() <= 8 * std::mem::size_of::<u64>());
+
+        assert!(!bits.contains(3));
+        bits.remove(3);
+        assert_eq!(bits.next_occupied(0), Some((1 << 40) + 3));
+        assert_eq!(bits.next_occupied((1 << 40) + 4), Some((1 << 40) + 200));
+        assert_eq!(bits.iter().count(), 2);
+    }
+}
@@ -0,0 +1,284 @@
+use std::vec::Vec;
+use crate::
+{
+    Index,
+    Key,
+    CapacityError,
+    exposed::IndexAllocator
+};
+
+/// A deferred operation on a `ClosedGenVec`
+#[derive(Eq, PartialEq, Debug)]
+pub enum Command<T, K = Index>
+{
+    /// Store `value` at the pre-reserved `index`
+    Insert
+    {
+        /// Index reserved when the insert was recorded
+        index: K,
+        /// Value to insert
+        value: T
+    },
+    /// Remove the value of `index`
+    Remove(K),
+    /// Overwrite the value of `index` with `value`
+    Replace
+    {
+        /// Index of the value to overwrite
+        index: K,
+        /// The new value
+        value: T
+    }
+}
+
+/// Reserves indices from the allocator of a `ClosedGenVec` while its values are borrowed
+///
+/// Returned by `ClosedGenVec::iter_mut_deferred`. The vec frees reserved indices that still hold
+/// no value the next time `iter_mut_deferred` is called
+#[derive(Debug)]
+pub struct IndexReserver<'a, K = Index>
+{
+    allocator: &'a mut IndexAllocator<K>,
+    reserved: &'a mut Vec<Index>
+}
+
+impl<'a, K: Key> IndexReserver<'a, K>
+{
+    pub(crate) fn new(allocator: &'a mut IndexAllocator<K>, reserved: &'a mut Vec<Index>) -> IndexReserver<'a, K>
+    {
+        IndexReserver { allocator, reserved }
+    }
+
+    /// Allocates an `Index` that will hold no value until a `Command::Insert` is applied to it
+    ///
+    /// # Panics
+    ///
+    /// If the maximum capacity has been reached. See `try_reserve`
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn reserve(&mut self) -> K
+    {
+        let index = self.allocator.allocate();
+        self.reserved.push(Index::from_key(index));
+        index
+    }
+
+    /// Allocates an `Index` that will hold no value until a `Command::Insert` is applied to it,
+    /// or an error if the maximum capacity has been reached
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn try_reserve(&mut self) -> Result<K, CapacityError>
+    {
+        let index = self.allocator.try_allocate()?;
+        self.reserved.push(Index::from_key(index));
+        Ok(index)
+    }
+}
+
+/// Buffer of inserts, removals and replacements to apply to a `ClosedGenVec` at once
+///
+/// Useful for modifying a vec while it's being iterated over
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::closed::{ClosedGenVec, Commands};
+///
+/// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+/// vec.insert(1);
+/// vec.insert(2);
+///
+/// let mut commands: Commands<i32> = Commands::new();
+/// let (iter, mut reserver) = vec.iter_mut_deferred();
+/// for (index, value) in iter
+/// {
+///     if *value == 1
+///     {
+///         commands.remove(index);
+///         commands.insert(&mut reserver, 10);
+///     }
+/// }
+///
+/// let skipped = vec.apply(commands);
+/// assert!(skipped.is_empty());
+/// assert_eq!(vec.iter().map(|(_, value)| *value).collect::<Vec<i32>>(), vec![2, 10]);
+/// ```
+#[derive(Debug)]
+pub struct Commands<T, K = Index>
+{
+    commands: Vec<Command<T, K>>
+}
+
+impl<T, K: Key> Default for Commands<T, K>
+{
+    fn default() -> Commands<T, K>
+    {
+        Commands::new()
+    }
+}
+
+impl<T, K: Key> Commands<T, K>
+{
+    /// Returns an empty `Commands` buffer
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Commands;
+    /// let commands: Commands<i32> = Commands::new();
+    /// ```
+    pub fn new() -> Commands<T, K>
+    {
+        Commands
+        {
+            commands: Vec::new()
+        }
+    }
+
+    /// Reserves an `Index` for `value` and records the insert
+    ///
+    /// The reserved `Index` holds no value and doesn't count towards the vec's length until
+    /// the commands are applied. If they haven't been applied by the next call to
+    /// `iter_mut_deferred`, the index is freed and `apply` hands the insert back as skipped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let mut commands: Commands<i32> = Commands::new();
+    ///
+    /// let (_, mut reserver) = vec.iter_mut_deferred();
+    /// let index: Index = commands.insert(&mut reserver, 5);
+    /// assert_eq!(vec.get(index), None);
+    ///
+    /// vec.apply(commands);
+    /// assert_eq!(vec.get(index), Some(&5));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the vec's maximum capacity has been reached. See `try_insert`
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert(&mut self, reserver: &mut IndexReserver<K>, value: T) -> K
+    {
+        let index = reserver.reserve();
+        self.commands.push(Command::Insert { index, value });
+        index
+    }
+
+    /// Reserves an `Index` for `value` and records the insert, or gives `value` back
+    /// if the vec's maximum capacity has been reached
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::with_max_capacity(1);
+    /// let mut commands: Commands<i32> = Commands::new();
+    ///
+    /// let (_, mut reserver) = vec.iter_mut_deferred();
+    /// assert!(commands.try_insert(&mut reserver, 5).is_ok());
+    /// assert_eq!(commands.try_insert(&mut reserver, 6).map_err(|error| error.into_value()), Err(6));
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn try_insert(&mut self, reserver: &mut IndexReserver<K>, value: T) -> Result<K, CapacityError<T>>
+    {
+        match reserver.try_reserve()
+        {
+            Ok(index) =>
+                {
+                    self.commands.push(Command::Insert { index, value });
+                    Ok(index)
+                },
+            Err(error) => Err(error.with_value(value))
+        }
+    }
+
+    /// Records the removal of `index`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(5);
+    ///
+    /// let mut commands: Commands<i32> = Commands::new();
+    /// commands.remove(index);
+    ///
+    /// vec.apply(commands);
+    /// assert!(!vec.contains(index));
+    /// ```
+    pub fn remove(&mut self, index: K)
+    {
+        self.commands.push(Command::Remove(index));
+    }
+
+    /// Records overwriting the value of `index` with `value`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(5);
+    ///
+    /// let mut commands: Commands<i32> = Commands::new();
+    /// commands.replace(index, 6);
+    ///
+    /// vec.apply(commands);
+    /// assert_eq!(vec.get(index), Some(&6));
+    /// ```
+    pub fn replace(&mut self, index: K, value: T)
+    {
+        self.commands.push(Command::Replace { index, value });
+    }
+
+    /// Number of recorded commands
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(5);
+    ///
+    /// let mut commands: Commands<i32> = Commands::new();
+    /// commands.replace(index, 6);
+    /// commands.remove(index);
+    /// assert_eq!(commands.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.commands.len()
+    }
+
+    /// Returns `true` if no commands have been recorded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Commands;
+    ///
+    /// let commands: Commands<i32> = Commands::new();
+    /// assert!(commands.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.commands.is_empty()
+    }
+
+    pub(crate) fn into_commands(self) -> Vec<Command<T, K>>
+    {
+        self.commands
+    }
+}
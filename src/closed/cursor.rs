@@ -0,0 +1,304 @@
+use crate::
+{
+    Index,
+    Key,
+    closed::ClosedGenVec
+};
+
+/// A cursor over the items of a `ClosedGenVec` that can remove and insert items while walking
+///
+/// Returned by `ClosedGenVec::cursor_mut`. Items are visited in slot order. Past the last item the cursor
+/// rests on a "ghost" position holding no item; moving next from it wraps around to the first item
+/// and moving back from it goes to the last item
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::closed::ClosedGenVec;
+///
+/// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+/// let index: Index = vec.insert(1);
+/// vec.insert(2);
+///
+/// let mut cursor = vec.cursor_mut();
+/// assert_eq!(cursor.current(), Some((index, &mut 1)));
+/// assert_eq!(cursor.peek_next().map(|(_, value)| *value), Some(2));
+///
+/// cursor.move_next();
+/// cursor.move_next();
+/// assert_eq!(cursor.current(), None);
+/// ```
+#[derive(Debug)]
+pub struct CursorMut<'a, T, K = Index>
+{
+    vec: &'a mut ClosedGenVec<T, K>,
+    slot: Option<usize>
+}
+
+impl<'a, T, K: Key> CursorMut<'a, T, K>
+{
+    pub(crate) fn new(vec: &'a mut ClosedGenVec<T, K>) -> CursorMut<'a, T, K>
+    {
+        let slot = vec.next_occupied(0);
+        CursorMut { vec, slot }
+    }
+
+    /// Slot of the item after the cursor, if any
+    fn next_slot(&self) -> Option<usize>
+    {
+        self.vec.next_occupied(self.slot.map_or(0, |slot| slot + 1))
+    }
+
+    /// Slot of the item before the cursor, if any
+    fn prev_slot(&self) -> Option<usize>
+    {
+        self.vec.prev_occupied(self.slot.unwrap_or(usize::MAX))
+    }
+
+    /// Returns the index and value of the item under the cursor, or `None` on the ghost position
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// if let Some((_, value)) = cursor.current()
+    /// {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(2));
+    /// ```
+    pub fn current(&mut self) -> Option<(K, &mut T)>
+    {
+        match self.slot
+        {
+            Some(slot) => self.vec.slot_mut(slot),
+            _ => None
+        }
+    }
+
+    /// Moves the cursor to the next item, or onto the ghost position past the last item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    /// vec.insert(2);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(2));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), None);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(1));
+    /// ```
+    pub fn move_next(&mut self)
+    {
+        self.slot = self.next_slot();
+    }
+
+    /// Moves the cursor to the previous item, or onto the ghost position before the first item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    /// vec.insert(2);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), None);
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(2));
+    /// ```
+    pub fn move_prev(&mut self)
+    {
+        self.slot = self.prev_slot();
+    }
+
+    /// Returns the index and value of the item after the cursor without moving it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    /// vec.insert(2);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// assert_eq!(cursor.peek_next().map(|(_, value)| *value), Some(2));
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(1));
+    /// ```
+    pub fn peek_next(&mut self) -> Option<(K, &mut T)>
+    {
+        let slot = self.next_slot()?;
+        self.vec.slot_mut(slot)
+    }
+
+    /// Returns the index and value of the item before the cursor without moving it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    /// vec.insert(2);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// assert_eq!(cursor.peek_prev(), None);
+    /// cursor.move_next();
+    /// assert_eq!(cursor.peek_prev().map(|(_, value)| *value), Some(1));
+    /// ```
+    pub fn peek_prev(&mut self) -> Option<(K, &mut T)>
+    {
+        let slot = self.prev_slot()?;
+        self.vec.slot_mut(slot)
+    }
+
+    /// Removes the item under the cursor, deallocating its index, and moves the cursor to the next item
+    ///
+    /// Returns `None` without moving if the cursor is on the ghost position
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(1);
+    /// vec.insert(2);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// assert_eq!(cursor.remove_current(), Some(1));
+    /// assert_eq!(cursor.current().map(|(_, value)| *value), Some(2));
+    ///
+    /// assert!(!vec.contains(index));
+    /// assert_eq!(vec.len(), 1);
+    /// ```
+    pub fn remove_current(&mut self) -> Option<T>
+    {
+        let (index, _) = self.current()?;
+        let removed = self.vec.remove(index);
+        self.move_next();
+        removed
+    }
+
+    /// Inserts `value` into the vec without moving the cursor, returning its `Index`
+    ///
+    /// The slot is chosen by the vec's allocator, so the new item may land before or after the cursor.
+    /// Items inserted after the cursor are visited when it gets there
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// let index: Index = cursor.insert(2);
+    /// assert_eq!(cursor.peek_next(), Some((index, &mut 2)));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the vec's maximum capacity has been reached. See `ClosedGenVec::try_insert`
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert(&mut self, value: T) -> K
+    {
+        self.vec.insert(value)
+    }
+}
+
+#[cfg(test)]
+mod cursor_tests
+{
+    use crate::Key;
+    use crate::closed::ClosedGenVec;
+
+    #[test]
+    fn remove_while_walking()
+    {
+        let mut vec = ClosedGenVec::new();
+        let indices: Vec<_> = (0..6).map(|value| vec.insert(value)).collect();
+
+        let mut cursor = vec.cursor_mut();
+        while let Some((_, value)) = cursor.current()
+        {
+            if *value % 3 == 0
+            {
+                cursor.remove_current();
+            }
+            else
+            {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(cursor.remove_current(), None);
+
+        assert_eq!(vec.len(), 4);
+        assert!(!vec.contains(indices[0]));
+        assert!(!vec.contains(indices[3]));
+        assert_eq!(vec.stats().total_frees, 2);
+
+        // Freed slots are reused by later inserts
+        let index = vec.insert(10);
+        assert_eq!(index.into_raw_parts(), (0, 1));
+    }
+
+    #[test]
+    fn insert_while_walking()
+    {
+        let mut vec = ClosedGenVec::new();
+        let first = vec.insert(0);
+        let second = vec.insert(1);
+        vec.remove(first);
+
+        let mut cursor = vec.cursor_mut();
+        assert_eq!(cursor.current().map(|(index, _)| index), Some(second));
+
+        // Reuses the freed slot before the cursor
+        let reused = cursor.insert(2);
+        assert_eq!(cursor.peek_prev().map(|(index, _)| index), Some(reused));
+        assert_eq!(cursor.peek_next(), None);
+
+        // Lands after the cursor
+        let appended = cursor.insert(3);
+        assert_eq!(cursor.peek_next().map(|(index, _)| index), Some(appended));
+        cursor.move_next();
+        assert_eq!(cursor.current().map(|(_, value)| *value), Some(3));
+        assert_eq!(vec.len(), 3);
+    }
+
+    #[test]
+    fn empty()
+    {
+        let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+        let mut cursor = vec.cursor_mut();
+        assert_eq!(cursor.current(), None);
+        cursor.move_next();
+        cursor.move_prev();
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.peek_next(), None);
+        assert_eq!(cursor.peek_prev(), None);
+    }
+}
@@ -1,26 +1,49 @@
+use std::{vec, mem};
 use crate::
 {
     Index,
+    Key,
+    IndexError,
+    Item,
+    CapacityError,
+    InsertAtError,
+    FromPartsError,
+    ReserveError,
+    Stats,
+    closed::{Command, Commands, CursorMut, IndexReserver},
     exposed::
     {
+        index_allocator,
         IndexAllocator,
         ExposedGenVec,
-        gen_vec::
-        {
-            IntoIter, Iter, IterMut
-        }
+        Quarantine,
+        gen_vec::Slot
     }
 };
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 /// Generationally indexed vector with an internal index allocator
-#[derive(Default, Debug)]
+///
+/// Values are accessed with keys of type `K`, which is `Index` unless another `Key` is given.
+/// Each slot's generation is kept by the allocator alone, so values are stored bare
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct ClosedGenVec<T>
+pub struct ClosedGenVec<T, K = Index>
 {
-    allocator: IndexAllocator,
-    vec: ExposedGenVec<T>
+    allocator: IndexAllocator<K>,
+    values: Vec<Option<T>>,
+    len: usize,
+    /// Indices handed out by an `IndexReserver` that may not hold a value yet
+    reserved: Vec<Index>
+}
+
+impl<T, K: Key> Default for ClosedGenVec<T, K>
+{
+    fn default() -> ClosedGenVec<T, K>
+    {
+        ClosedGenVec::with_key()
+    }
 }
 
 impl<T> ClosedGenVec<T>
@@ -35,11 +58,7 @@ impl<T> ClosedGenVec<T>
     /// ```
     pub fn new() -> ClosedGenVec<T>
     {
-        ClosedGenVec
-        {
-            allocator: IndexAllocator::new(),
-            vec: ExposedGenVec::new()
-        }
+        ClosedGenVec::with_key()
     }
 
     /// Returns a `ClosedGenVec` with initial capacity of `capacity`
@@ -54,14 +73,282 @@ impl<T> ClosedGenVec<T>
     /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::with_capacity(5);
     /// ```
     pub fn with_capacity(capacity: usize) -> ClosedGenVec<T>
+    {
+        ClosedGenVec::with_capacity_and_key(capacity)
+    }
+
+    /// Returns an empty `ClosedGenVec` that will never hold more than `max_capacity` items at once
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::with_max_capacity(1);
+    /// assert_eq!(vec.max_capacity(), Some(1));
+    ///
+    /// assert!(vec.try_insert(0).is_ok());
+    /// assert!(vec.try_insert(1).is_err());
+    /// ```
+    pub fn with_max_capacity(max_capacity: usize) -> ClosedGenVec<T>
+    {
+        ClosedGenVec::with_max_capacity_and_key(max_capacity)
+    }
+}
+
+impl<T, K: Key> ClosedGenVec<T, K>
+{
+    /// Returns an empty `ClosedGenVec` that hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let mut vec: ClosedGenVec<i32, EntityId> = ClosedGenVec::with_key();
+    /// let id: EntityId = vec.insert(5);
+    /// ```
+    pub fn with_key() -> ClosedGenVec<T, K>
     {
         ClosedGenVec
         {
-            allocator: IndexAllocator::with_capacity(capacity),
-            vec: ExposedGenVec::with_capacity(capacity)
+            allocator: IndexAllocator::with_key(),
+            values: Vec::new(),
+            len: 0,
+            reserved: Vec::new()
         }
     }
 
+    /// Returns a `ClosedGenVec` with initial capacity of `capacity` that hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let vec: ClosedGenVec<i32, EntityId> = ClosedGenVec::with_capacity_and_key(5);
+    /// assert!(vec.capacity() >= 5);
+    /// ```
+    pub fn with_capacity_and_key(capacity: usize) -> ClosedGenVec<T, K>
+    {
+        ClosedGenVec
+        {
+            allocator: IndexAllocator::with_capacity_and_key(capacity),
+            values: Vec::with_capacity(capacity),
+            len: 0,
+            reserved: Vec::new()
+        }
+    }
+
+    /// Returns an empty `ClosedGenVec` that will never hold more than `max_capacity` items and hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let vec: ClosedGenVec<i32, EntityId> = ClosedGenVec::with_max_capacity_and_key(1);
+    /// assert_eq!(vec.max_capacity(), Some(1));
+    /// ```
+    pub fn with_max_capacity_and_key(max_capacity: usize) -> ClosedGenVec<T, K>
+    {
+        ClosedGenVec
+        {
+            allocator: IndexAllocator::with_max_capacity_and_key(max_capacity),
+            values: Vec::new(),
+            len: 0,
+            reserved: Vec::new()
+        }
+    }
+
+    /// Returns a `ClosedGenVec` made of an `allocator` and the `vec` of values it indexes
+    ///
+    /// Every value in `vec` must be at an index that is active in `allocator`.
+    /// Active indices without a value are freed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut values: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// values.set(index, 3);
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::from_parts(allocator, values).expect("parts agree");
+    /// assert_eq!(vec.remove(index), Some(3));
+    ///
+    /// let (allocator, mut values) = vec.into_parts();
+    /// values.set(index, 4);
+    /// let error = ClosedGenVec::from_parts(allocator, values).expect_err("index was freed");
+    /// assert_eq!(error.index(), index);
+    /// ```
+    pub fn from_parts(mut allocator: IndexAllocator<K>, vec: ExposedGenVec<T, K>) -> Result<ClosedGenVec<T, K>, FromPartsError<T, K>>
+    {
+        let orphan = vec.iter()
+                        .map(|(index, _)| index)
+                        .find(|index| !allocator.is_active(*index));
+        if let Some(index) = orphan
+        {
+            return Err(FromPartsError::new(index, allocator, vec));
+        }
+
+        // Every value is at an active index, so the allocator's generations already describe them
+        let values: Vec<Option<T>> = vec.into_slots()
+                                        .into_iter()
+                                        .map(|slot| match slot
+                                        {
+                                            Slot::Occupied(item) => Some(item.value),
+                                            Slot::Vacant { .. } => None
+                                        })
+                                        .collect();
+        let len = values.iter().filter(|value| value.is_some()).count();
+        let empty: Vec<K> = allocator.iter()
+                                     .filter(|index| !matches!(values.get(Index::from_key(*index).index), Some(Some(_))))
+                                     .collect();
+        for index in empty
+        {
+            allocator.deallocate(index);
+        }
+        Ok(ClosedGenVec { allocator, values, len, reserved: Vec::new() })
+    }
+
+    /// Splits the vec into its allocator and the values it indexes
+    ///
+    /// Every index stays valid in the returned parts, so they can be shared with other `ExposedGenVec`s
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut vec: ClosedGenVec<&str> = ClosedGenVec::new();
+    /// let index: Index = vec.insert("player");
+    ///
+    /// let (allocator, names): (IndexAllocator, ExposedGenVec<&str>) = vec.into_parts();
+    /// assert!(allocator.is_active(index));
+    /// assert_eq!(names.get(index), Some(&"player"));
+    ///
+    /// let mut healths: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// healths.set(index, 100);
+    /// ```
+    pub fn into_parts(mut self) -> (IndexAllocator<K>, ExposedGenVec<T, K>)
+    {
+        self.release_reserved();
+        let allocator = self.allocator;
+        let slots = self.values
+                        .into_iter()
+                        .enumerate()
+                        .map(|(slot, value)|
+                            {
+                                let generation = allocator.generation_at(slot);
+                                match value
+                                {
+                                    Some(value) => Slot::Occupied(Item { value, generation }),
+                                    _ => Slot::Vacant { generation }
+                                }
+                            })
+                        .collect();
+        (allocator, ExposedGenVec::from_slots(slots))
+    }
+
+    /// Returns the allocator handing out the vec's indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// assert!(vec.allocator().is_active(index));
+    /// assert_eq!(vec.allocator().num_active(), 1);
+    /// ```
+    pub fn allocator(&self) -> &IndexAllocator<K>
+    {
+        &self.allocator
+    }
+
+    /// The maximum number of items the vec can hold at once, if bounded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// assert_eq!(vec.max_capacity(), None);
+    ///
+    /// let vec: ClosedGenVec<i32> = ClosedGenVec::with_max_capacity(10);
+    /// assert_eq!(vec.max_capacity(), Some(10));
+    /// ```
+    pub fn max_capacity(&self) -> Option<usize>
+    {
+        self.allocator.max_capacity()
+    }
+
+    /// Sets how long removed slots must wait before they can be reused.
+    /// See `IndexAllocator::set_quarantine`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::exposed::Quarantine;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.set_quarantine(Some(Quarantine::Ticks(1)));
+    ///
+    /// let index: Index = vec.insert(0);
+    /// vec.remove(index);
+    /// assert_eq!(vec.stats().quarantined, 1);
+    /// ```
+    pub fn set_quarantine(&mut self, quarantine: Option<Quarantine>)
+    {
+        self.allocator.set_quarantine(quarantine);
+    }
+
+    /// Advances the vec by one tick, releasing slots whose `Quarantine::Ticks` delay has passed.
+    /// See `IndexAllocator::advance`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::exposed::Quarantine;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.set_quarantine(Some(Quarantine::Ticks(1)));
+    ///
+    /// let index: Index = vec.insert(0);
+    /// vec.remove(index);
+    /// vec.advance();
+    /// assert_eq!(vec.stats().quarantined, 0);
+    /// assert_eq!(vec.stats().free_list_len, 1);
+    /// ```
+    pub fn advance(&mut self)
+    {
+        self.allocator.advance();
+    }
+
     /// Number of active `Item`s within the vec
     ///
     /// The internal item vec may actually be larger depending on the number of freed indices
@@ -81,7 +368,7 @@ impl<T> ClosedGenVec<T>
     /// ```
     pub fn len(&self) -> usize
     {
-        self.allocator.num_active()
+        self.len
     }
 
     /// Returns `true` if there are no active items
@@ -103,7 +390,35 @@ impl<T> ClosedGenVec<T>
     /// ```
     pub fn is_empty(&self) -> bool
     {
-        self.allocator.num_active() == 0
+        self.len == 0
+    }
+
+    /// Returns a snapshot of allocation totals, fragmentation and memory usage
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, Stats};
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// vec.insert(1);
+    /// vec.remove(index);
+    ///
+    /// let stats: Stats = vec.stats();
+    /// assert_eq!(stats.total_allocations, 2);
+    /// assert_eq!(stats.total_frees, 1);
+    /// assert_eq!(stats.fragmentation, 0.5);
+    /// assert!(stats.items_bytes > 0);
+    /// ```
+    pub fn stats(&self) -> Stats
+    {
+        Stats
+        {
+            items_bytes: self.values.capacity() * mem::size_of::<Option<T>>(),
+            ..self.allocator.stats()
+        }
     }
 
     /// Reserved capacity within the vec
@@ -143,7 +458,31 @@ impl<T> ClosedGenVec<T>
     pub fn reserve(&mut self, additional: usize)
     {
         self.allocator.reserve(additional);
-        self.vec.reserve(additional);
+        self.values.reserve(additional);
+    }
+
+    /// Reserves extra space for *at least* `additional` more elements,
+    /// returning an error instead of aborting if the space can't be reserved
+    ///
+    /// Fails without reserving anything if `additional` more items would exceed the maximum capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::ReserveError;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::with_max_capacity(4);
+    /// assert_eq!(vec.try_reserve(4), Ok(()));
+    /// assert!(vec.capacity() >= 4);
+    ///
+    /// assert_eq!(vec.try_reserve(5), Err(ReserveError::MaxCapacityExceeded { max_capacity: 4 }));
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ReserveError>
+    {
+        self.allocator.try_reserve(additional)?;
+        self.values.try_reserve(additional)?;
+        Ok(())
     }
 
     /// Insert `value` and return an associated `Index`
@@ -157,13 +496,82 @@ impl<T> ClosedGenVec<T>
     ///
     /// let index: Index = vec.insert(23);
     /// ```
-    pub fn insert(&mut self, value: T) -> Index
+    ///
+    /// # Panics
+    ///
+    /// If the maximum capacity has been reached. See `try_insert`
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert(&mut self, value: T) -> K
     {
         let index = self.allocator.allocate();
-        self.vec.set(index, value);
+        self.put(index, value);
         index
     }
 
+    /// Insert `value` and return an associated `Index`, or give `value` back
+    /// if the maximum capacity has been reached
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::with_max_capacity(1);
+    ///
+    /// let index: Index = vec.try_insert(23).expect("Should have room for one item");
+    ///
+    /// let error = vec.try_insert(24).expect_err("Should be full");
+    /// assert_eq!(error.into_value(), 24);
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn try_insert(&mut self, value: T) -> Result<K, CapacityError<T>>
+    {
+        match self.allocator.try_allocate()
+        {
+            Ok(index) =>
+                {
+                    self.put(index, value);
+                    Ok(index)
+                },
+            Err(error) => Err(error.with_value(value))
+        }
+    }
+
+    /// Insert `value` at exactly `index`, growing the vec if its slot hasn't been reached yet
+    ///
+    /// Useful for mirroring another vec. Returns the value of an older generation
+    /// that was replaced (if any), or gives `value` back if `index` couldn't be allocated.
+    /// See `IndexAllocator::allocate_at`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, AllocateAtError};
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut server: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// server.insert(0);
+    /// let index: Index = server.insert(1);
+    ///
+    /// let mut client: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// assert_eq!(client.insert_at(index, 1), Ok(None));
+    /// assert_eq!(client.get(index), Some(&1));
+    /// assert_eq!(client.len(), 1);
+    ///
+    /// let error = client.insert_at(index, 2).expect_err("Index is already occupied");
+    /// assert_eq!(error.error(), AllocateAtError::Occupied);
+    /// assert_eq!(error.into_value(), 2);
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert_at(&mut self, index: K, value: T) -> Result<Option<T>, InsertAtError<T>>
+    {
+        match self.allocator.allocate_at(index)
+        {
+            Ok(()) => Ok(self.put(index, value)),
+            Err(error) => Err(InsertAtError::new(error, value))
+        }
+    }
+
     /// Returns `true` if the `index` points to a valid item within
     ///
     /// # Examples
@@ -180,9 +588,9 @@ impl<T> ClosedGenVec<T>
     /// vec.remove(index);
     /// assert!(!vec.contains(index));
     /// ```
-    pub fn contains(&self, index: Index) -> bool
+    pub fn contains(&self, index: K) -> bool
     {
-        self.allocator.is_active(index)
+        self.get(index).is_some()
     }
 
     /// Returns the value of `index` if `index` is valid
@@ -204,11 +612,47 @@ impl<T> ClosedGenVec<T>
     /// vec.remove(index);
     /// assert!(!vec.contains(index));
     /// ```
-    pub fn remove(&mut self, index: Index) -> Option<T>
+    pub fn remove(&mut self, index: K) -> Option<T>
+    {
+        self.try_remove(index).ok()
+    }
+
+    /// Returns the value of `index`, or why `index` is invalid
+    ///
+    /// On success, `index` is added to the pool of free indices
+    /// available for reuse. An index reserved through an `IndexReserver` whose insert
+    /// was never applied holds no value, but is freed all the same
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    ///
+    /// let index: Index = vec.insert(124);
+    /// assert_eq!(vec.try_remove(index), Ok(124));
+    /// assert_eq!(vec.try_remove(index), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_remove(&mut self, index: K) -> Result<T, IndexError>
     {
-        let removed = self.vec.remove(index);
-        self.allocator.deallocate(index);
-        removed
+        match self.take(index)
+        {
+            Ok(removed) =>
+                {
+                    self.allocator.deallocate(index);
+                    Ok(removed)
+                },
+            Err(error) =>
+                {
+                    if self.allocator.is_active(index)
+                    {
+                        self.allocator.deallocate(index);
+                    }
+                    Err(error)
+                }
+        }
     }
 
     /// Free all items
@@ -233,6 +677,9 @@ impl<T> ClosedGenVec<T>
     /// ```
     pub fn clear(&mut self)
     {
+        self.values.clear();
+        self.len = 0;
+        self.reserved.clear();
         self.allocator.deallocate_all();
     }
 
@@ -250,101 +697,547 @@ impl<T> ClosedGenVec<T>
     /// let value: Option<&i32> = vec.get(index);
     /// assert_eq!(value, Some(&23));
     /// ```
-    pub fn get(&self, index: Index) -> Option<&T>
+    pub fn get(&self, index: K) -> Option<&T>
+    {
+        self.try_get(index).ok()
+    }
+
+    /// Returns an immutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::ClosedGenVec;
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    ///
+    /// let index: Index = vec.insert(23);
+    /// assert_eq!(vec.try_get(index), Ok(&23));
+    ///
+    /// vec.remove(index);
+    /// vec.insert(24);
+    /// assert_eq!(vec.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+    /// ```
+    pub fn try_get(&self, index: K) -> Result<&T, IndexError>
+    {
+        let (slot, generation) = index.into_raw_parts();
+        let current = self.allocator.generation_at(slot);
+        match self.values.get(slot)
+        {
+            Some(value) if current == generation => value.as_ref().ok_or(IndexError::Vacant),
+            Some(value) => Err(mismatch(current, generation, value.is_some())),
+            None => Err(IndexError::OutOfBounds)
+        }
+    }
+
+    /// Returns a mutable reference to the value of `index` if `index` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    ///
+    /// let index: Index = vec.insert(23);
+    ///
+    /// let mut value: Option<&mut i32> = vec.get_mut(index);
+    /// assert_eq!(value, Some(&mut 23));
+    ///
+    /// if let Some(value) = value
+    /// {
+    ///     *value = 0;
+    /// }
+    ///
+    /// let value: Option<&i32> = vec.get(index);
+    /// assert_eq!(value, Some(&0));
+    /// ```
+    pub fn get_mut(&mut self, index: K) -> Option<&mut T>
     {
-        self.vec.get(index)
+        self.try_get_mut(index).ok()
+    }
+
+    /// Returns a mutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::ClosedGenVec;
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    ///
+    /// let index: Index = vec.insert(23);
+    ///
+    /// if let Ok(value) = vec.try_get_mut(index)
+    /// {
+    ///     *value = 0;
+    /// }
+    /// assert_eq!(vec.get(index), Some(&0));
+    ///
+    /// vec.remove(index);
+    /// assert_eq!(vec.try_get_mut(index), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_get_mut(&mut self, index: K) -> Result<&mut T, IndexError>
+    {
+        let (slot, generation) = index.into_raw_parts();
+        let current = self.allocator.generation_at(slot);
+        match self.values.get_mut(slot)
+        {
+            Some(value) if current == generation => value.as_mut().ok_or(IndexError::Vacant),
+            Some(value) => Err(mismatch(current, generation, value.is_some())),
+            None => Err(IndexError::OutOfBounds)
+        }
+    }
+
+    /// Exchanges the values of `a` and `b` in place, leaving both indices pointing to their slots
+    ///
+    /// Fails, leaving the vec untouched, with the reason the first invalid index couldn't be used
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let a: Index = vec.insert(0);
+    /// let b: Index = vec.insert(1);
+    ///
+    /// assert_eq!(vec.swap(a, b), Ok(()));
+    /// assert_eq!(vec[a], 1);
+    /// assert_eq!(vec[b], 0);
+    ///
+    /// vec.remove(b);
+    /// assert_eq!(vec.swap(a, b), Err(IndexError::Vacant));
+    /// ```
+    pub fn swap(&mut self, a: K, b: K) -> Result<(), IndexError>
+    {
+        self.try_get(a)?;
+        self.try_get(b)?;
+        self.values.swap(Index::from_key(a).index, Index::from_key(b).index);
+        Ok(())
+    }
+
+    /// Replaces the value of a valid `index`, returning the previous value
+    ///
+    /// If `index` is invalid, `value` is handed back as the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    ///
+    /// assert_eq!(vec.replace(index, 1), Ok(0));
+    /// assert_eq!(vec[index], 1);
+    ///
+    /// vec.remove(index);
+    /// assert_eq!(vec.replace(index, 2), Err(2));
+    /// ```
+    pub fn replace(&mut self, index: K, value: T) -> Result<T, T>
+    {
+        match self.get_mut(index)
+        {
+            Some(current) => Ok(mem::replace(current, value)),
+            _ => Err(value)
+        }
+    }
+
+    /// Returns an iterator of immutable references to the vec elements
+    ///
+    /// Each iterator step returns (Index, &T)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(0);
+    /// vec.insert(1);
+    ///
+    /// for (index, value) in vec // vec.iter() is valid too
+    /// {
+    ///     println!("Index: {:?}, Value: {}", index, value);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, K>
+    {
+        Iter
+        {
+            indices: self.allocator.iter(),
+            values: &self.values
+        }
+    }
+
+    /// Returns an iterator of mutable references to the vec elements
+    ///
+    /// Each iterator step returns (Index, &mut T)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(0);
+    /// vec.insert(1);
+    ///
+    /// for (index, value) in vec.iter_mut()
+    /// {
+    ///     *value = 0;
+    /// }
+    ///
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K>
+    {
+        IterMut
+        {
+            indices: Indices::Allocator(self.allocator.iter()),
+            values: &mut self.values,
+            offset: 0
+        }
+    }
+
+    /// Consumes the vec, converting every value with `f` into a vec of `U`
+    ///
+    /// The allocator moves over as is, free list included, so every index into
+    /// this vec stays valid in the new one and later inserts reuse the same slots
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// let index: Index = vec.insert(2);
+    ///
+    /// let names: ClosedGenVec<String> = vec.map(|_, value| format!("#{}", value));
+    /// assert_eq!(names[index], "#2");
+    /// ```
+    pub fn map<U, F>(self, mut f: F) -> ClosedGenVec<U, K>
+        where F: FnMut(K, T) -> U
+    {
+        let allocator = self.allocator;
+        let values = self.values
+                         .into_iter()
+                         .enumerate()
+                         .map(|(slot, value)| value.map(|value| f(K::from_raw_parts(slot, allocator.generation_at(slot)), value)))
+                         .collect();
+        ClosedGenVec
+        {
+            allocator,
+            values,
+            len: self.len,
+            reserved: self.reserved
+        }
+    }
+
+    /// Returns a cursor positioned at the first item, for walking the vec
+    /// and removing or inserting items along the way
+    ///
+    /// See `CursorMut`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ClosedGenVec;
+    ///
+    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
+    /// vec.insert(1);
+    /// vec.insert(2);
+    /// vec.insert(3);
+    ///
+    /// let mut cursor = vec.cursor_mut();
+    /// while let Some((_, value)) = cursor.current()
+    /// {
+    ///     if *value % 2 == 0
+    ///     {
+    ///         cursor.remove_current();
+    ///     }
+    ///     else
+    ///     {
+    ///         *value *= 10;
+    ///         cursor.move_next();
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(vec.iter().map(|(_, value)| *value).collect::<Vec<i32>>(), vec![10, 30]);
+    /// ```
+    pub fn cursor_mut(&mut self) -> CursorMut<'_, T, K>
+    {
+        CursorMut::new(self)
+    }
+
+    /// Stores `value` in the slot of `index`, returning the value it replaced
+    fn put(&mut self, index: K, value: T) -> Option<T>
+    {
+        let slot = Index::from_key(index).index;
+        if self.values.len() <= slot
+        {
+            self.values.resize_with(slot + 1, | | None);
+        }
+        let replaced = self.values[slot].replace(value);
+        if replaced.is_none()
+        {
+            self.len += 1;
+        }
+        replaced
+    }
+
+    /// Takes the value at `index` out of its slot, leaving the allocator untouched
+    fn take(&mut self, index: K) -> Result<T, IndexError>
+    {
+        let (slot, generation) = index.into_raw_parts();
+        let current = self.allocator.generation_at(slot);
+        match self.values.get_mut(slot)
+        {
+            Some(value) if current == generation =>
+                {
+                    let removed = value.take().ok_or(IndexError::Vacant)?;
+                    self.len -= 1;
+                    Ok(removed)
+                },
+            Some(value) => Err(mismatch(current, generation, value.is_some())),
+            None => Err(IndexError::OutOfBounds)
+        }
+    }
+
+    /// Frees the reserved indices that are still waiting for a value
+    fn release_reserved(&mut self)
+    {
+        for index in self.reserved.drain(..)
+        {
+            let index: K = index.into_key();
+            if self.allocator.is_active(index) && !matches!(self.values.get(Index::from_key(index).index), Some(Some(_)))
+            {
+                self.allocator.deallocate(index);
+            }
+        }
+    }
+
+    /// First slot holding a value at or after `from`
+    pub(crate) fn next_occupied(&self, from: usize) -> Option<usize>
+    {
+        let mut from = from;
+        loop
+        {
+            let (slot, _) = self.allocator.next_active(from)?;
+            // Reserved indices may not hold a value yet
+            if let Some(Some(_)) = self.values.get(slot)
+            {
+                return Some(slot);
+            }
+            from = slot + 1;
+        }
+    }
+
+    /// Last slot holding a value before `before`
+    pub(crate) fn prev_occupied(&self, before: usize) -> Option<usize>
+    {
+        let mut before = before;
+        loop
+        {
+            let (slot, _) = self.allocator.prev_active(before)?;
+            if let Some(Some(_)) = self.values.get(slot)
+            {
+                return Some(slot);
+            }
+            before = slot;
+        }
     }
 
-    /// Returns a mutable reference to the value of `index` if `index` is valid
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use gen_vec::Index;
-    /// use gen_vec::closed::ClosedGenVec;
-    /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
-    ///
-    /// let index: Index = vec.insert(23);
-    ///
-    /// let mut value: Option<&mut i32> = vec.get_mut(index);
-    /// assert_eq!(value, Some(&mut 23));
-    ///
-    /// if let Some(value) = value
-    /// {
-    ///     *value = 0;
-    /// }
-    ///
-    /// let value: Option<&i32> = vec.get(index);
-    /// assert_eq!(value, Some(&0));
-    /// ```
-    pub fn get_mut(&mut self, index: Index) -> Option<&mut T>
+    /// Key and value held in `slot`, if any
+    pub(crate) fn slot_mut(&mut self, slot: usize) -> Option<(K, &mut T)>
     {
-        self.vec.get_mut(index)
+        let generation = self.allocator.generation_at(slot);
+        self.values.get_mut(slot)?.as_mut().map(|value| (K::from_raw_parts(slot, generation), value))
     }
 
-    /// Returns an iterator of immutable references to the vec elements
+    /// Returns an iterator of mutable references to the vec elements alongside
+    /// an `IndexReserver` for recording inserts into `Commands` during iteration
     ///
-    /// Each iterator step returns (Index, &T)
+    /// Each iterator step returns (Index, &mut T)
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::closed::{ClosedGenVec, Commands};
     ///
     /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
     /// vec.insert(0);
     /// vec.insert(1);
     ///
-    /// for (index, value) in vec // vec.iter() is valid too
+    /// let mut commands: Commands<i32> = Commands::new();
+    /// let (iter, mut reserver) = vec.iter_mut_deferred();
+    /// for (index, value) in iter
     /// {
-    ///     println!("Index: {:?}, Value: {}", index, value);
+    ///     *value += 1;
+    ///     commands.insert(&mut reserver, *value * 10);
     /// }
+    ///
+    /// vec.apply(commands);
+    /// assert_eq!(vec.len(), 4);
     /// ```
-    pub fn iter(&self) -> Iter<T>
+    pub fn iter_mut_deferred(&mut self) -> (IterMut<'_, T, K>, IndexReserver<'_, K>)
     {
-        self.vec.iter()
+        self.release_reserved();
+        let iter = IterMut
+        {
+            indices: Indices::Collected(self.allocator.iter().collect::<Vec<K>>().into_iter()),
+            values: &mut self.values,
+            offset: 0
+        };
+        (iter, IndexReserver::new(&mut self.allocator, &mut self.reserved))
     }
 
-    /// Returns an iterator of mutable references to the vec elements
+    /// Applies every recorded command in order
     ///
-    /// Each iterator step returns (Index, &mut T)
+    /// Returns the commands that became no-ops because their indices were no longer valid,
+    /// handing back any values they held
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::closed::ClosedGenVec;
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ClosedGenVec, Command, Commands};
     ///
     /// let mut vec: ClosedGenVec<i32> = ClosedGenVec::new();
-    /// vec.insert(0);
-    /// vec.insert(1);
+    /// let index: Index = vec.insert(0);
     ///
-    /// for (index, value) in vec.iter_mut()
-    /// {
-    ///     *value = 0;
-    /// }
+    /// let mut commands: Commands<i32> = Commands::new();
+    /// commands.remove(index);
+    /// commands.replace(index, 1);
     ///
+    /// let skipped = vec.apply(commands);
+    /// assert_eq!(skipped, vec![Command::Replace { index, value: 1 }]);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<T>
+    pub fn apply(&mut self, commands: Commands<T, K>) -> Vec<Command<T, K>>
+    {
+        let mut skipped = Vec::new();
+        for command in commands.into_commands()
+        {
+            match command
+            {
+                Command::Insert { index, value } if self.allocator.is_active(index) && self.get(index).is_none() =>
+                    {
+                        self.put(index, value);
+                    },
+                Command::Remove(index) if self.contains(index) =>
+                    {
+                        self.remove(index);
+                    },
+                Command::Replace { index, value } if self.get(index).is_some() =>
+                    {
+                        self.put(index, value);
+                    },
+                command => skipped.push(command)
+            }
+        }
+
+        let (allocator, values) = (&self.allocator, &self.values);
+        self.reserved.retain(|index| allocator.is_active(index.into_key()) && !matches!(values.get(index.index), Some(Some(_))));
+        skipped
+    }
+}
+
+/// Error for an index of generation `requested` into a slot last allocated at generation `current`
+fn mismatch(current: usize, requested: usize, occupied: bool) -> IndexError
+{
+    if occupied
+    {
+        IndexError::mismatch(current, requested)
+    }
+    else if current > requested
+    {
+        IndexError::Stale { current_generation: current }
+    }
+    else
+    {
+        IndexError::Vacant
+    }
+}
+
+/// Struct for consuming a `ClosedGenVec` into an iterator
+#[derive(Debug)]
+pub struct IntoIter<T, K = Index>
+{
+    indices: index_allocator::IntoIter<K>,
+    values: vec::IntoIter<Option<T>>,
+    next: usize
+}
+
+impl<T, K: Key> Iterator for IntoIter<T, K>
+{
+    type Item = (K, T);
+
+    fn next(&mut self) -> Option<Self::Item>
     {
-        self.vec.iter_mut()
+        loop
+        {
+            let index = self.indices.next()?;
+            let slot = Index::from_key(index).index;
+            let value = self.values.nth(slot.checked_sub(self.next)?)?;
+            self.next = slot + 1;
+            // Reserved indices may not hold a value yet
+            if let Some(value) = value
+            {
+                return Some((index, value));
+            }
+        }
     }
 }
 
-impl<T> IntoIterator for ClosedGenVec<T>
+impl<T, K: Key> IntoIterator for ClosedGenVec<T, K>
 {
-    type Item = (Index, T);
-    type IntoIter = IntoIter<T>;
+    type Item = (K, T);
+    type IntoIter = IntoIter<T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
-        self.vec.into_iter()
+        IntoIter
+        {
+            indices: self.allocator.into_iter(),
+            values: self.values.into_iter(),
+            next: 0
+        }
+    }
+}
+
+/// Struct for creating an iterator over an immutable `ClosedGenVec` reference
+#[derive(Debug)]
+pub struct Iter<'a, T: 'a, K = Index>
+{
+    indices: index_allocator::Iter<'a, K>,
+    values: &'a [Option<T>]
+}
+
+impl<'a, T, K: Key> Iterator for Iter<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop
+        {
+            let index = self.indices.next()?;
+            // Reserved indices may not hold a value yet
+            if let Some(Some(value)) = self.values.get(Index::from_key(index).index)
+            {
+                return Some((index, value));
+            }
+        }
     }
 }
 
-impl<'a, T> IntoIterator for &'a ClosedGenVec<T>
+impl<'a, T, K: Key> IntoIterator for &'a ClosedGenVec<T, K>
 {
-    type Item = (Index, &'a T);
-    type IntoIter = Iter<'a, T>;
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
@@ -352,10 +1245,70 @@ impl<'a, T> IntoIterator for &'a ClosedGenVec<T>
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut ClosedGenVec<T>
+/// Active indices visited by an `IterMut`
+#[derive(Debug)]
+enum Indices<'a, K>
+{
+    Allocator(index_allocator::Iter<'a, K>),
+    Collected(vec::IntoIter<K>)
+}
+
+impl<'a, K: Key> Iterator for Indices<'a, K>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        match self
+        {
+            Indices::Allocator(internal) => internal.next(),
+            Indices::Collected(internal) => internal.next()
+        }
+    }
+}
+
+/// Struct for creating an iterator over a mutable `ClosedGenVec` reference
+#[derive(Debug)]
+pub struct IterMut<'a, T: 'a, K = Index>
 {
-    type Item = (Index, &'a mut T);
-    type IntoIter = IterMut<'a, T>;
+    indices: Indices<'a, K>,
+    values: &'a mut [Option<T>],
+    offset: usize
+}
+
+impl<'a, T: 'a, K: Key> Iterator for IterMut<'a, T, K>
+{
+    type Item = (K, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop
+        {
+            let index = self.indices.next()?;
+            let slot = Index::from_key(index).index;
+            if slot < self.offset || slot - self.offset >= self.values.len()
+            {
+                continue;
+            }
+
+            // Split off every slot up to and including `slot`, since later steps only look further ahead
+            let values = mem::take(&mut self.values);
+            let (visited, rest) = values.split_at_mut(slot - self.offset + 1);
+            self.values = rest;
+            self.offset = slot + 1;
+            // Reserved indices may not hold a value yet
+            if let Some(Some(value)) = visited.last_mut()
+            {
+                return Some((index, value));
+            }
+        }
+    }
+}
+
+impl<'a, T, K: Key> IntoIterator for &'a mut ClosedGenVec<T, K>
+{
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
@@ -363,27 +1316,28 @@ impl<'a, T> IntoIterator for &'a mut ClosedGenVec<T>
     }
 }
 
-impl<T> std::ops::Index<Index> for ClosedGenVec<T>
+impl<T, K: Key> std::ops::Index<K> for ClosedGenVec<T, K>
 {
     type Output = T;
 
-    fn index(&self, index: Index) -> &Self::Output
+    fn index(&self, index: K) -> &Self::Output
     {
-        self.get(index).expect(format!("Index should be valid: {:?}", index).as_str())
+        self.try_get(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
     }
 }
 
-impl<T> std::ops::IndexMut<Index> for ClosedGenVec<T>
+impl<T, K: Key> std::ops::IndexMut<K> for ClosedGenVec<T, K>
 {
-    fn index_mut(&mut self, index: Index) -> &mut Self::Output
+    fn index_mut(&mut self, index: K) -> &mut Self::Output
     {
-        self.get_mut(index).expect(format!("Index should be valid: {:?}", index).as_str())
+        self.try_get_mut(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::closed::ClosedGenVec;
+    use crate::closed::{ClosedGenVec, Command, Commands};
+    use crate::{Index, AllocateAtError, IndexError, ReserveError};
 
     #[test]
     fn insert()
@@ -400,6 +1354,67 @@ mod tests {
         assert_eq!(vec.len(), 2);
     }
 
+    #[test]
+    fn try_insert()
+    {
+        let mut vec = ClosedGenVec::with_max_capacity(2);
+        let index = vec.try_insert(3).expect("Should have room");
+        vec.try_insert(4).expect("Should have room");
+
+        let error = vec.try_insert(5).expect_err("Should be full");
+        assert_eq!(error.max_capacity(), 2);
+        assert_eq!(error.into_value(), 5);
+        assert_eq!(vec.len(), 2);
+
+        vec.remove(index);
+        let index = vec.try_insert(6).expect("Removed slot should be reused");
+        assert_eq!(vec.get(index), Some(&6));
+    }
+
+    #[test]
+    fn try_reserve()
+    {
+        let mut vec = ClosedGenVec::<i32>::with_max_capacity(2);
+        assert_eq!(vec.try_reserve(2), Ok(()));
+        assert_eq!(vec.try_reserve(3), Err(ReserveError::MaxCapacityExceeded { max_capacity: 2 }));
+
+        let mut vec = ClosedGenVec::<i32>::new();
+        assert_eq!(vec.try_reserve(24), Ok(()));
+        assert!(vec.capacity() >= 24);
+    }
+
+    #[test]
+    fn insert_at()
+    {
+        let mut vec = ClosedGenVec::new();
+        let index = Index { index: 2, generation: 3 };
+        assert_eq!(vec.insert_at(index, 1), Ok(None));
+        assert_eq!(vec.get(index), Some(&1));
+        assert_eq!(vec.len(), 1);
+
+        let index1 = vec.insert(2);
+        assert_eq!(index1, Index { index: 0, generation: 1 });
+        assert_eq!(vec.len(), 2);
+
+        let error = vec.insert_at(Index { index: 2, generation: 2 }, 3).expect_err("Index is stale");
+        assert_eq!(error.error(), AllocateAtError::Stale { current_generation: 3 });
+        assert_eq!(error.into_value(), 3);
+
+        let index2 = Index { index: 2, generation: 4 };
+        assert_eq!(vec.insert_at(index2, 4), Ok(Some(1)));
+        assert!(!vec.contains(index));
+        assert_eq!(vec.get(index2), Some(&4));
+        assert_eq!(vec.len(), 2);
+
+        assert_eq!(vec.remove(index2), Some(4));
+        assert_eq!(vec.len(), 1);
+
+        let error = vec.insert_at(index2, 5).expect_err("Index was removed");
+        assert_eq!(error.error(), AllocateAtError::Stale { current_generation: 4 });
+        assert_eq!(vec.get(index2), None);
+        assert_eq!(vec.len(), 1);
+    }
+
     #[test]
     fn get()
     {
@@ -444,6 +1459,155 @@ mod tests {
         assert_eq!(vec.len(), 1);
     }
 
+    #[test]
+    fn try_get()
+    {
+        let mut vec = ClosedGenVec::new();
+        let index = vec.insert(3);
+        assert_eq!(vec.try_get(index), Ok(&3));
+
+        if let Ok(value) = vec.try_get_mut(index)
+        {
+            *value = 1;
+        }
+        assert_eq!(vec.try_get(index), Ok(&1));
+
+        vec.remove(index);
+        assert_eq!(vec.try_get(index), Err(IndexError::Vacant));
+
+        let index1 = vec.insert(4);
+        assert_eq!(vec.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(vec.try_get_mut(index1), Ok(&mut 4));
+    }
+
+    #[test]
+    fn try_remove()
+    {
+        let mut vec = ClosedGenVec::new();
+        let index = vec.insert(3);
+        assert_eq!(vec.try_remove(index), Ok(3));
+        assert_eq!(vec.try_remove(index), Err(IndexError::Vacant));
+        assert_eq!(vec.len(), 0);
+
+        vec.insert(4);
+        assert_eq!(vec.try_remove(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(vec.len(), 1);
+    }
+
+    #[test]
+    fn remove_reserved()
+    {
+        let mut vec = ClosedGenVec::new();
+        vec.insert(1);
+
+        // The commands holding the reserved index are dropped without being applied
+        let mut commands = Commands::new();
+        let (_, mut reserver) = vec.iter_mut_deferred();
+        let reserved = commands.insert(&mut reserver, 2);
+        drop(commands);
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec.iter().count(), 1);
+        assert!(!vec.contains(reserved));
+
+        assert_eq!(vec.try_remove(reserved), Err(IndexError::OutOfBounds));
+        assert_eq!(vec.len(), 1);
+        assert!(!vec.allocator().is_active(reserved));
+
+        let reused = vec.insert(3);
+        assert_eq!(reused.index, reserved.index);
+        assert_eq!(reused.generation, reserved.generation + 1);
+    }
+
+    #[test]
+    fn release_reserved()
+    {
+        let mut vec = ClosedGenVec::new();
+        vec.insert(1);
+
+        let mut commands = Commands::new();
+        let (_, mut reserver) = vec.iter_mut_deferred();
+        let reserved = commands.insert(&mut reserver, 2);
+        assert!(vec.allocator().is_active(reserved));
+
+        // The next round frees reservations that were never applied, so the late insert is handed back
+        vec.iter_mut_deferred();
+        assert!(!vec.allocator().is_active(reserved));
+        assert_eq!(vec.allocator().num_active(), 1);
+        assert_eq!(vec.apply(commands), vec![Command::Insert { index: reserved, value: 2 }]);
+        assert_eq!(vec.len(), 1);
+
+        let mut commands = Commands::new();
+        let (_, mut reserver) = vec.iter_mut_deferred();
+        let reserved = commands.insert(&mut reserver, 3);
+        let (allocator, _) = vec.into_parts();
+        assert!(!allocator.is_active(reserved));
+        assert_eq!(allocator.num_active(), 1);
+    }
+
+    #[test]
+    fn apply()
+    {
+        let mut vec = ClosedGenVec::new();
+        let index = vec.insert(1);
+        let index1 = vec.insert(2);
+        let index2 = vec.insert(3);
+
+        let mut commands = Commands::new();
+        let mut inserted = Vec::new();
+        let (iter, mut reserver) = vec.iter_mut_deferred();
+        for (index, value) in iter
+        {
+            match *value
+            {
+                1 => commands.remove(index),
+                2 => commands.replace(index, 20),
+                _ => inserted.push(commands.insert(&mut reserver, *value * 10))
+            }
+            *value += 100;
+        }
+        assert_eq!(commands.len(), 3);
+
+        // Reserved indices are allocated but hold no value until applied
+        assert_eq!(vec.len(), 3);
+        assert!(vec.allocator().is_active(inserted[0]));
+        assert!(!vec.contains(inserted[0]));
+
+        let skipped = vec.apply(commands);
+        assert!(skipped.is_empty());
+        assert!(!vec.contains(index));
+        assert_eq!(vec.get(index1), Some(&20));
+        assert_eq!(vec.get(index2), Some(&103));
+        assert_eq!(vec.get(inserted[0]), Some(&30));
+        assert_eq!(vec.len(), 3);
+    }
+
+    #[test]
+    fn apply_stale()
+    {
+        let mut vec = ClosedGenVec::new();
+        let index = vec.insert(1);
+
+        let mut commands = Commands::new();
+        let (_, mut reserver) = vec.iter_mut_deferred();
+        let reserved = commands.insert(&mut reserver, 2);
+        commands.remove(index);
+        commands.remove(index);
+        commands.replace(index, 3);
+
+        let skipped = vec.apply(commands);
+        assert_eq!(skipped, vec![Command::Remove(index), Command::Replace { index, value: 3 }]);
+        assert_eq!(vec.get(reserved), Some(&2));
+
+        let mut commands = Commands::new();
+        let (_, mut reserver) = vec.iter_mut_deferred();
+        let reserved = commands.insert(&mut reserver, 4);
+        vec.clear();
+
+        let skipped = vec.apply(commands);
+        assert_eq!(skipped, vec![Command::Insert { index: reserved, value: 4 }]);
+        assert!(vec.is_empty());
+    }
+
     #[test]
     fn clear()
     {
@@ -454,12 +1618,35 @@ mod tests {
         vec.clear();
         assert!(!vec.contains(index));
         assert!(!vec.contains(index1));
+        assert_eq!(vec.get(index), None);
+        assert_eq!(vec.iter().count(), 0);
 
         assert_eq!(vec.len(), 0);
         let index1 = vec.insert(1);
         assert!(vec.contains(index1));
     }
 
+    #[test]
+    fn stats()
+    {
+        let mut vec = ClosedGenVec::<u64>::new();
+        let stats = vec.stats();
+        assert_eq!(stats.items_bytes, 0);
+        assert_eq!(stats.fragmentation, 1.0);
+
+        let index = vec.insert(1);
+        vec.insert(2);
+        vec.remove(index);
+
+        let stats = vec.stats();
+        assert_eq!(stats.total_allocations, 2);
+        assert_eq!(stats.total_frees, 1);
+        assert_eq!(stats.peak_active, 2);
+        assert_eq!(stats.free_list_len, 1);
+        assert_eq!(stats.items_bytes, vec.values.capacity() * std::mem::size_of::<Option<u64>>());
+        assert!(stats.active_indices_bytes > 0);
+    }
+
     #[test]
     fn len()
     {
@@ -592,4 +1779,79 @@ mod tests {
 
         assert_eq!(vec[index], 5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "index is stale, slot is now at generation 1")]
+    fn index_stale()
+    {
+        let mut vec = ClosedGenVec::<i32>::new();
+        let index = vec.insert(4);
+        vec.remove(index);
+        vec.insert(5);
+
+        let _ = vec[index];
+    }
+
+    #[test]
+    fn replace()
+    {
+        let mut vec = ClosedGenVec::<i32>::new();
+        let index = vec.insert(0);
+        vec.remove(index);
+        let index1 = vec.insert(1);
+
+        assert_eq!(vec.replace(index, 2), Err(2));
+        assert_eq!(vec.replace(index1, 2), Ok(1));
+        assert_eq!(vec[index1], 2);
+    }
+
+    #[test]
+    fn map_keeps_allocator()
+    {
+        let mut vec = ClosedGenVec::<i32>::new();
+        let index = vec.insert(0);
+        let index1 = vec.insert(1);
+        vec.remove(index);
+
+        let mut vec = vec.map(|_, value| value.to_string());
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec[index1], "1");
+        assert_eq!(vec.stats().total_frees, 1);
+
+        // The freed slot is still the next one handed out
+        let index2 = vec.insert("2".to_string());
+        assert_eq!(index2, Index::from((0, 1)));
+        assert!(!vec.contains(index));
+    }
+
+    #[test]
+    fn parts()
+    {
+        let mut vec = ClosedGenVec::<i32>::new();
+        let index = vec.insert(0);
+        let index1 = vec.insert(1);
+        vec.remove(index);
+
+        let (mut allocator, values) = vec.into_parts();
+        assert_eq!(allocator.num_free(), 1);
+
+        // Allocated indices without a value are freed
+        let index2 = allocator.allocate();
+        let mut vec = ClosedGenVec::from_parts(allocator, values).expect("Parts should agree");
+        assert_eq!(vec.len(), 1);
+        assert_eq!(vec[index1], 1);
+        assert!(!vec.allocator().is_active(index2));
+        assert_eq!(vec.insert(3), Index::from((0, 2)));
+
+        let (allocator, mut values) = vec.into_parts();
+        // Slots take their generation from the allocator
+        assert_eq!(values.try_get(index2), Err(IndexError::Stale { current_generation: 2 }));
+        values.set(Index::from((5, 0)), 5);
+        let error = ClosedGenVec::from_parts(allocator, values).expect_err("Index 5 isn't allocated");
+        assert_eq!(error.index(), Index::from((5, 0)));
+        assert_eq!(error.to_string(), "Index { index: 5, generation: 0 } holds a value but isn't active in the allocator");
+
+        let (_, values) = error.into_parts();
+        assert_eq!(values.get(index1), Some(&1));
+    }
+}
@@ -19,4 +19,14 @@
 //! ```
 
 mod gen_vec;
-pub use self::gen_vec::*;
\ No newline at end of file
+pub use self::gen_vec::*;
+mod commands;
+pub use self::commands::*;
+mod replication;
+pub use self::replication::*;
+mod cursor;
+pub use self::cursor::*;
+mod table;
+pub use self::table::*;
+mod tree;
+pub use self::tree::*;
@@ -0,0 +1,696 @@
+use std::
+{
+    collections::BTreeSet,
+    vec::Vec
+};
+use crate::
+{
+    Index,
+    Key,
+    DeltaError,
+    closed::{ClosedGenVec, Iter, IterMut}
+};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A single change to a `ReplicatedGenVec`
+#[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Change<T, K = Index>
+{
+    /// `value` was inserted at `index`
+    Insert
+    {
+        /// Index the value was inserted at
+        index: K,
+        /// The inserted value
+        value: T
+    },
+    /// The value of `index` was removed
+    Remove(K),
+    /// The value of `index` was modified
+    Modify
+    {
+        /// Index of the modified value
+        index: K,
+        /// The modified value
+        value: T
+    }
+}
+
+/// Every change made to a `ReplicatedGenVec` between two checkpoints
+///
+/// Removals are listed before inserts so reused slots are freed before they're reinserted
+#[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Delta<T, K = Index>
+{
+    sequence: u64,
+    changes: Vec<Change<T, K>>
+}
+
+impl<T, K: Key> Delta<T, K>
+{
+    /// Position of the delta within the stream of deltas, starting at `0`
+    pub fn sequence(&self) -> u64
+    {
+        self.sequence
+    }
+
+    /// The changes made since the previous delta
+    pub fn changes(&self) -> &[Change<T, K>]
+    {
+        &self.changes
+    }
+
+    /// Returns `true` if nothing changed since the previous delta
+    pub fn is_empty(&self) -> bool
+    {
+        self.changes.is_empty()
+    }
+}
+
+/// `ClosedGenVec` that records its changes so they can be replicated to a `Mirror`
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::closed::{ReplicatedGenVec, Mirror, Delta};
+///
+/// let mut server: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+/// let mut client: Mirror<i32> = Mirror::new();
+///
+/// let index: Index = server.insert(5);
+/// let delta: Delta<i32> = server.checkpoint();
+/// client.apply(delta).expect("Delta should be next in sequence");
+/// assert_eq!(client.vec().get(index), Some(&5));
+///
+/// if let Some(value) = server.get_mut(index)
+/// {
+///     *value = 6;
+/// }
+/// client.apply(server.checkpoint()).expect("Delta should be next in sequence");
+/// assert_eq!(client.vec().get(index), Some(&6));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "T: Deserialize<'de>, K: Key + Deserialize<'de>")))]
+pub struct ReplicatedGenVec<T, K = Index>
+{
+    vec: ClosedGenVec<T, K>,
+    sequence: u64,
+    inserted: BTreeSet<K>,
+    modified: BTreeSet<K>,
+    removed: Vec<K>
+}
+
+impl<T, K: Key> Default for ReplicatedGenVec<T, K>
+{
+    fn default() -> ReplicatedGenVec<T, K>
+    {
+        ReplicatedGenVec::with_key()
+    }
+}
+
+impl<T> ReplicatedGenVec<T>
+{
+    /// Returns an empty `ReplicatedGenVec`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// ```
+    pub fn new() -> ReplicatedGenVec<T>
+    {
+        ReplicatedGenVec::with_key()
+    }
+}
+
+impl<T, K: Key> ReplicatedGenVec<T, K>
+{
+    /// Returns an empty `ReplicatedGenVec` that hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let mut vec: ReplicatedGenVec<i32, EntityId> = ReplicatedGenVec::with_key();
+    /// let id: EntityId = vec.insert(5);
+    /// ```
+    pub fn with_key() -> ReplicatedGenVec<T, K>
+    {
+        ReplicatedGenVec
+        {
+            vec: ClosedGenVec::with_key(),
+            sequence: 0,
+            inserted: BTreeSet::new(),
+            modified: BTreeSet::new(),
+            removed: Vec::new()
+        }
+    }
+
+    /// The underlying vec
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// vec.insert(0);
+    /// assert_eq!(vec.vec().len(), 1);
+    /// ```
+    pub fn vec(&self) -> &ClosedGenVec<T, K>
+    {
+        &self.vec
+    }
+
+    /// Insert `value`, record the insert and return an associated `Index`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// assert_eq!(vec.get(index), Some(&0));
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert(&mut self, value: T) -> K
+    {
+        let index = self.vec.insert(value);
+        self.inserted.insert(index);
+        index
+    }
+
+    /// Removes and returns the value of `index` if `index` is valid and records the removal
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// assert_eq!(vec.remove(index), Some(0));
+    /// ```
+    pub fn remove(&mut self, index: K) -> Option<T>
+    {
+        let removed = self.vec.remove(index)?;
+        self.modified.remove(&index);
+        // Values inserted and removed between checkpoints never need to be replicated
+        if !self.inserted.remove(&index)
+        {
+            self.removed.push(index);
+        }
+        Some(removed)
+    }
+
+    /// Returns `true` if the `index` points to a valid item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// assert!(vec.contains(index));
+    /// ```
+    pub fn contains(&self, index: K) -> bool
+    {
+        self.vec.contains(index)
+    }
+
+    /// Returns an immutable reference to the value of `index` if `index` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    /// assert_eq!(vec.get(index), Some(&0));
+    /// ```
+    pub fn get(&self, index: K) -> Option<&T>
+    {
+        self.vec.get(index)
+    }
+
+    /// Returns a mutable reference to the value of `index` if `index` is valid
+    ///
+    /// The value is recorded as modified whether or not it's actually changed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    ///
+    /// if let Some(value) = vec.get_mut(index)
+    /// {
+    ///     *value = 1;
+    /// }
+    /// assert_eq!(vec.get(index), Some(&1));
+    /// ```
+    pub fn get_mut(&mut self, index: K) -> Option<&mut T>
+    {
+        let value = self.vec.get_mut(index)?;
+        if !self.inserted.contains(&index)
+        {
+            self.modified.insert(index);
+        }
+        Some(value)
+    }
+
+    /// Returns an iterator of immutable references to the vec elements
+    ///
+    /// Each iterator step returns (Index, &T)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// vec.insert(0);
+    /// vec.insert(1);
+    ///
+    /// for (index, value) in vec.iter()
+    /// {
+    ///     println!("Index: {:?}, Value: {}", index, value);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, K>
+    {
+        self.vec.iter()
+    }
+
+    /// Returns an iterator of mutable references to the vec elements
+    ///
+    /// Every value is recorded as modified
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// vec.insert(0);
+    /// vec.insert(1);
+    ///
+    /// for (index, value) in vec.iter_mut()
+    /// {
+    ///     *value += 1;
+    /// }
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K>
+    {
+        for (index, _) in self.vec.iter()
+        {
+            if !self.inserted.contains(&index)
+            {
+                self.modified.insert(index);
+            }
+        }
+        self.vec.iter_mut()
+    }
+
+    /// Number of active values within the vec
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// vec.insert(0);
+    /// assert_eq!(vec.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.vec.len()
+    }
+
+    /// Returns `true` if there are no active values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::ReplicatedGenVec;
+    ///
+    /// let vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// assert!(vec.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.vec.is_empty()
+    }
+}
+
+impl<T: Clone, K: Key> ReplicatedGenVec<T, K>
+{
+    /// Returns every change recorded since the previous checkpoint and starts recording anew
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::{ReplicatedGenVec, Change};
+    ///
+    /// let mut vec: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let index: Index = vec.insert(0);
+    ///
+    /// let delta = vec.checkpoint();
+    /// assert_eq!(delta.sequence(), 0);
+    /// assert_eq!(delta.changes(), &[Change::Insert { index, value: 0 }]);
+    ///
+    /// vec.remove(index);
+    /// let delta = vec.checkpoint();
+    /// assert_eq!(delta.sequence(), 1);
+    /// assert_eq!(delta.changes(), &[Change::Remove(index)]);
+    /// ```
+    pub fn checkpoint(&mut self) -> Delta<T, K>
+    {
+        let vec = &self.vec;
+        let value_of = |index: &K| vec.get(*index).cloned().map(|value| (*index, value));
+
+        let mut changes: Vec<Change<T, K>> = self.removed.drain(..).map(Change::Remove).collect();
+        changes.extend(self.inserted.iter()
+                                    .filter_map(value_of)
+                                    .map(|(index, value)| Change::Insert { index, value }));
+        changes.extend(self.modified.iter()
+                                    .filter_map(value_of)
+                                    .map(|(index, value)| Change::Modify { index, value }));
+        self.inserted.clear();
+        self.modified.clear();
+
+        let delta = Delta { sequence: self.sequence, changes };
+        self.sequence += 1;
+        delta
+    }
+}
+
+/// Copy of a `ReplicatedGenVec` kept up to date by applying its deltas in order
+///
+/// Values are stored at the same `Index`es as the original
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::{Index, DeltaError};
+/// use gen_vec::closed::{ReplicatedGenVec, Mirror};
+///
+/// let mut server: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+/// let mut client: Mirror<i32> = Mirror::new();
+///
+/// let index: Index = server.insert(5);
+/// let first = server.checkpoint();
+/// let second = server.checkpoint();
+///
+/// assert_eq!(client.apply(second.clone()), Err(DeltaError::Gap { expected: 0, found: 1 }));
+/// assert_eq!(client.apply(first), Ok(()));
+/// assert_eq!(client.apply(second), Ok(()));
+/// assert_eq!(client.vec().get(index), Some(&5));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Mirror<T, K = Index>
+{
+    vec: ClosedGenVec<T, K>,
+    sequence: u64
+}
+
+impl<T, K: Key> Default for Mirror<T, K>
+{
+    fn default() -> Mirror<T, K>
+    {
+        Mirror::with_key()
+    }
+}
+
+impl<T> Mirror<T>
+{
+    /// Returns an empty `Mirror` expecting the first delta
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Mirror;
+    /// let mirror: Mirror<i32> = Mirror::new();
+    /// assert_eq!(mirror.sequence(), 0);
+    /// ```
+    pub fn new() -> Mirror<T>
+    {
+        Mirror::with_key()
+    }
+}
+
+impl<T, K: Key> Mirror<T, K>
+{
+    /// Returns an empty `Mirror` of a vec with keys of type `K`, expecting the first delta
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::Mirror;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let mirror: Mirror<i32, EntityId> = Mirror::with_key();
+    /// assert_eq!(mirror.sequence(), 0);
+    /// ```
+    pub fn with_key() -> Mirror<T, K>
+    {
+        Mirror
+        {
+            vec: ClosedGenVec::with_key(),
+            sequence: 0
+        }
+    }
+
+    /// The mirrored vec
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Mirror;
+    ///
+    /// let mirror: Mirror<i32> = Mirror::new();
+    /// assert!(mirror.vec().is_empty());
+    /// ```
+    pub fn vec(&self) -> &ClosedGenVec<T, K>
+    {
+        &self.vec
+    }
+
+    /// Sequence number of the next delta to be applied
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::{ReplicatedGenVec, Mirror};
+    ///
+    /// let mut server: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let mut client: Mirror<i32> = Mirror::new();
+    ///
+    /// client.apply(server.checkpoint()).expect("Delta should be next in sequence");
+    /// assert_eq!(client.sequence(), 1);
+    /// ```
+    pub fn sequence(&self) -> u64
+    {
+        self.sequence
+    }
+
+    /// Applies the changes of `delta` if it's the next delta in sequence
+    ///
+    /// If a change conflicts with the mirror's state, the changes before it remain applied
+    /// and the mirror should be considered diverged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, DeltaError};
+    /// use gen_vec::closed::{ReplicatedGenVec, Mirror};
+    ///
+    /// let mut server: ReplicatedGenVec<i32> = ReplicatedGenVec::new();
+    /// let mut client: Mirror<i32> = Mirror::new();
+    ///
+    /// let index: Index = server.insert(5);
+    /// let delta = server.checkpoint();
+    /// assert_eq!(client.apply(delta.clone()), Ok(()));
+    /// assert_eq!(client.apply(delta), Err(DeltaError::OutOfOrder { expected: 1, found: 0 }));
+    /// ```
+    pub fn apply(&mut self, delta: Delta<T, K>) -> Result<(), DeltaError<K>>
+    {
+        if delta.sequence > self.sequence
+        {
+            return Err(DeltaError::Gap { expected: self.sequence, found: delta.sequence });
+        }
+        if delta.sequence < self.sequence
+        {
+            return Err(DeltaError::OutOfOrder { expected: self.sequence, found: delta.sequence });
+        }
+
+        for change in delta.changes
+        {
+            match change
+            {
+                Change::Remove(index) => { self.vec.remove(index); },
+                Change::Modify { index, value } if self.vec.contains(index) => { self.vec[index] = value; },
+                Change::Insert { index, value } | Change::Modify { index, value } =>
+                    {
+                        self.vec.insert_at(index, value)
+                                .map_err(|error| DeltaError::Conflict { index, error: error.error() })?;
+                    }
+            }
+        }
+
+        self.sequence += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::closed::*;
+    use crate::{Index, AllocateAtError, DeltaError};
+
+    #[test]
+    fn checkpoint()
+    {
+        let mut vec = ReplicatedGenVec::new();
+        let index = vec.insert(0);
+        let index1 = vec.insert(1);
+        let index2 = vec.insert(2);
+        vec.remove(index2);
+        *vec.get_mut(index1).expect("Index should be valid") = 10;
+
+        let delta = vec.checkpoint();
+        assert_eq!(delta.sequence(), 0);
+        assert_eq!(delta.changes(), &[Change::Insert { index, value: 0 }, Change::Insert { index: index1, value: 10 }]);
+
+        vec.remove(index);
+        let index3 = vec.insert(3);
+        for (_, value) in vec.iter_mut()
+        {
+            *value += 1;
+        }
+        vec.remove(index1);
+        let delta = vec.checkpoint();
+        assert_eq!(delta.sequence(), 1);
+        assert_eq!(delta.changes(), &[
+            Change::Remove(index),
+            Change::Remove(index1),
+            Change::Insert { index: index3, value: 4 }
+        ]);
+
+        let delta = vec.checkpoint();
+        assert_eq!(delta.sequence(), 2);
+        assert!(delta.is_empty());
+    }
+
+    #[test]
+    fn mirror()
+    {
+        let mut server = ReplicatedGenVec::new();
+        let mut client = Mirror::new();
+
+        let indices: Vec<Index> = (0..4).map(|i| server.insert(i)).collect();
+        client.apply(server.checkpoint()).expect("Delta should be next in sequence");
+
+        server.remove(indices[0]);
+        server.remove(indices[2]);
+        let reused = server.insert(10);
+        *server.get_mut(indices[3]).expect("Index should be valid") = 30;
+        client.apply(server.checkpoint()).expect("Delta should be next in sequence");
+
+        assert_eq!(client.sequence(), 2);
+        assert_eq!(client.vec().len(), server.len());
+        for (index, value) in server.iter()
+        {
+            assert_eq!(client.vec().get(index), Some(value));
+        }
+        assert!(!client.vec().contains(indices[0]));
+        assert_eq!(client.vec().get(reused), Some(&10));
+    }
+
+    #[test]
+    fn mirror_sequence()
+    {
+        let mut server = ReplicatedGenVec::<i32>::new();
+        let mut client = Mirror::new();
+
+        let first = server.checkpoint();
+        let second = server.checkpoint();
+        assert_eq!(client.apply(second.clone()), Err(DeltaError::Gap { expected: 0, found: 1 }));
+        assert_eq!(client.apply(first.clone()), Ok(()));
+        assert_eq!(client.apply(first), Err(DeltaError::OutOfOrder { expected: 1, found: 0 }));
+        assert_eq!(client.apply(second), Ok(()));
+        assert_eq!(client.sequence(), 2);
+    }
+
+    #[test]
+    fn mirror_conflict()
+    {
+        let mut server = ReplicatedGenVec::new();
+        let mut other = ReplicatedGenVec::new();
+        let mut client = Mirror::new();
+
+        server.insert(0);
+        client.apply(server.checkpoint()).expect("Delta should be next in sequence");
+
+        // A delta from another server's stream follows in sequence but inserts into an occupied slot
+        other.checkpoint();
+        let index = other.insert(1);
+        assert_eq!(client.apply(other.checkpoint()), Err(DeltaError::Conflict { index, error: AllocateAtError::Occupied }));
+        assert_eq!(client.sequence(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_delta()
+    {
+        let mut server = ReplicatedGenVec::new();
+        let mut client = Mirror::new();
+
+        let index = server.insert(String::from("zero"));
+        let index1 = server.insert(String::from("one"));
+        let first = server.checkpoint();
+        server.remove(index);
+        *server.get_mut(index1).expect("Index should be valid") = String::from("ten");
+        let index2 = server.insert(String::from("two"));
+
+        for delta in [first, server.checkpoint()]
+        {
+            let json = serde_json::to_string(&delta).expect("Delta should serialize");
+            let received: Delta<String> = serde_json::from_str(&json).expect("Delta should deserialize");
+            assert_eq!(received, delta);
+            client.apply(received).expect("Delta should be next in sequence");
+        }
+
+        assert_eq!(client.sequence(), 2);
+        assert!(!client.vec().contains(index));
+        assert_eq!(client.vec().get(index1).map(String::as_str), Some("ten"));
+        assert_eq!(client.vec().get(index2).map(String::as_str), Some("two"));
+    }
+}
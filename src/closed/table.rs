@@ -0,0 +1,572 @@
+use std::
+{
+    any::{self, Any, TypeId},
+    collections::HashMap,
+    fmt
+};
+use crate::
+{
+    Index,
+    Key,
+    exposed::{IndexAllocator, ExposedGenVec}
+};
+
+/// A typed column of a `Table`, erased so columns of any type can be stored together
+trait Column<K>
+{
+    /// Drops the value of `index`, if any
+    fn remove(&mut self, index: K);
+
+    /// Name of the column's value type
+    fn type_name(&self) -> &'static str;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: 'static, K: Key + 'static> Column<K> for ExposedGenVec<T, K>
+{
+    fn remove(&mut self, index: K)
+    {
+        ExposedGenVec::remove(self, index);
+    }
+
+    fn type_name(&self) -> &'static str
+    {
+        any::type_name::<T>()
+    }
+
+    fn as_any(&self) -> &dyn Any
+    {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any>
+    {
+        self
+    }
+}
+
+/// Several `ExposedGenVec` columns bound to one internal `IndexAllocator`
+///
+/// Each column holds values of one type. An index of the table is a row that may have a value in any
+/// number of columns. Despawning a row frees its index and drops its values from every column
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::closed::Table;
+///
+/// struct Position(f32, f32);
+/// struct Name(&'static str);
+///
+/// let mut table: Table = Table::new();
+/// let player: Index = table.insert((Position(0.0, 1.0), Name("player")));
+/// let rock: Index = table.insert((Position(4.0, 2.0),));
+///
+/// assert_eq!(table.get::<Name>(player).map(|name| name.0), Some("player"));
+/// assert!(table.get::<Name>(rock).is_none());
+///
+/// table.despawn(player);
+/// assert!(table.get::<Position>(player).is_none());
+/// assert_eq!(table.column::<Position>().map(|positions| positions.iter().count()), Some(1));
+/// ```
+pub struct Table<K = Index>
+{
+    allocator: IndexAllocator<K>,
+    columns: HashMap<TypeId, Box<dyn Column<K>>>
+}
+
+impl<K: Key + 'static> Default for Table<K>
+{
+    fn default() -> Table<K>
+    {
+        Table::with_key()
+    }
+}
+
+impl<K: fmt::Debug> fmt::Debug for Table<K>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let mut columns: Vec<&str> = self.columns.values().map(|column| column.type_name()).collect();
+        columns.sort_unstable();
+        f.debug_struct("Table")
+         .field("allocator", &self.allocator)
+         .field("columns", &columns)
+         .finish()
+    }
+}
+
+impl Table
+{
+    /// Returns an empty `Table` with no columns
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let table: Table = Table::new();
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn new() -> Table
+    {
+        Table::with_key()
+    }
+}
+
+impl<K: Key + 'static> Table<K>
+{
+    /// Returns an empty `Table` with no columns that hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::Table;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let mut table: Table<EntityId> = Table::with_key();
+    /// let id: EntityId = table.insert((1u8,));
+    /// ```
+    pub fn with_key() -> Table<K>
+    {
+        Table
+        {
+            allocator: IndexAllocator::with_key(),
+            columns: HashMap::new()
+        }
+    }
+
+    /// Number of rows in the table
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// table.insert((0i32,));
+    /// table.insert(());
+    /// assert_eq!(table.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.allocator.num_active()
+    }
+
+    /// Returns `true` if the table has no rows
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let table: Table = Table::new();
+    /// assert!(table.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// Returns the allocator handing out the table's indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert(());
+    /// assert!(table.allocator().is_active(index));
+    /// ```
+    pub fn allocator(&self) -> &IndexAllocator<K>
+    {
+        &self.allocator
+    }
+
+    /// Adds an empty column of `T` values, returning `false` if the table already has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// assert!(table.add_column::<f32>());
+    /// assert!(!table.add_column::<f32>());
+    /// assert!(table.has_column::<f32>());
+    /// ```
+    pub fn add_column<T: 'static>(&mut self) -> bool
+    {
+        if self.has_column::<T>()
+        {
+            return false;
+        }
+        self.column_or_insert::<T>();
+        true
+    }
+
+    /// Returns `true` if the table has a column of `T` values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// assert!(!table.has_column::<f32>());
+    /// table.insert((1.0f32,));
+    /// assert!(table.has_column::<f32>());
+    /// ```
+    pub fn has_column<T: 'static>(&self) -> bool
+    {
+        self.columns.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Removes the column of `T` values from the table and returns it, if there was one
+    ///
+    /// The returned column's indices stay valid for as long as they are active in the table
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    /// use gen_vec::exposed::ExposedGenVec;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1.0f32,));
+    ///
+    /// let column: ExposedGenVec<f32> = table.remove_column::<f32>().expect("column exists");
+    /// assert_eq!(column.get(index), Some(&1.0));
+    /// assert!(!table.has_column::<f32>());
+    /// ```
+    pub fn remove_column<T: 'static>(&mut self) -> Option<ExposedGenVec<T, K>>
+    {
+        let column = self.columns.remove(&TypeId::of::<T>())?;
+        column.into_any().downcast().ok().map(|column| *column)
+    }
+
+    /// Returns the column of `T` values, if the table has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// table.insert((1u8,));
+    /// table.insert((2u8,));
+    ///
+    /// let sum: u8 = table.column::<u8>().map(|column| column.iter().map(|(_, value)| value).sum()).unwrap_or(0);
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn column<T: 'static>(&self) -> Option<&ExposedGenVec<T, K>>
+    {
+        self.columns.get(&TypeId::of::<T>())?.as_any().downcast_ref()
+    }
+
+    /// Returns the column of `T` values mutably, if the table has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8,));
+    ///
+    /// if let Some(column) = table.column_mut::<u8>()
+    /// {
+    ///     for (_, value) in column.iter_mut()
+    ///     {
+    ///         *value += 1;
+    ///     }
+    /// }
+    /// assert_eq!(table.get::<u8>(index), Some(&2));
+    /// ```
+    pub fn column_mut<T: 'static>(&mut self) -> Option<&mut ExposedGenVec<T, K>>
+    {
+        self.columns.get_mut(&TypeId::of::<T>())?.as_any_mut().downcast_mut()
+    }
+
+    /// Returns the column of `T` values mutably, adding it first if the table doesn't have one
+    fn column_or_insert<T: 'static>(&mut self) -> &mut ExposedGenVec<T, K>
+    {
+        self.columns
+            .entry(TypeId::of::<T>())
+            .or_insert_with(| | Box::new(ExposedGenVec::<T, K>::with_key()))
+            .as_any_mut()
+            .downcast_mut()
+            .unwrap_or_else(| | panic!("Column of {} holds another type", any::type_name::<T>()))
+    }
+
+    /// Adds a row holding every value of `bundle` and returns its index
+    ///
+    /// Columns missing for any of the bundle's types are added
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8, "one", 1.0f32));
+    ///
+    /// assert_eq!(table.get::<u8>(index), Some(&1));
+    /// assert_eq!(table.get::<&str>(index), Some(&"one"));
+    /// assert_eq!(table.get::<f32>(index), Some(&1.0));
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn insert<B: Bundle<K>>(&mut self, bundle: B) -> K
+    {
+        let index = self.allocator.allocate();
+        bundle.set_all(self, index);
+        index
+    }
+
+    /// Removes the row of `index`, dropping its values from every column and freeing the index for reuse
+    ///
+    /// Returns `false` if `index` isn't an active row
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8, "one"));
+    ///
+    /// assert!(table.despawn(index));
+    /// assert!(!table.despawn(index));
+    /// assert!(!table.contains(index));
+    /// assert_eq!(table.get::<u8>(index), None);
+    /// ```
+    pub fn despawn(&mut self, index: K) -> bool
+    {
+        if !self.allocator.is_active(index)
+        {
+            return false;
+        }
+
+        for column in self.columns.values_mut()
+        {
+            column.remove(index);
+        }
+        self.allocator.deallocate(index);
+        true
+    }
+
+    /// Returns `true` if `index` is an active row
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert(());
+    /// assert!(table.contains(index));
+    /// ```
+    pub fn contains(&self, index: K) -> bool
+    {
+        self.allocator.is_active(index)
+    }
+
+    /// Sets the `T` value of an active row, returning the previous value (if any)
+    ///
+    /// The column is added if the table doesn't have one. If `index` isn't an active row, `value` is handed back as the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert(());
+    ///
+    /// assert_eq!(table.set(index, 1u8), Ok(None));
+    /// assert_eq!(table.set(index, 2u8), Ok(Some(1)));
+    ///
+    /// table.despawn(index);
+    /// assert_eq!(table.set(index, 3u8), Err(3));
+    /// ```
+    pub fn set<T: 'static>(&mut self, index: K, value: T) -> Result<Option<T>, T>
+    {
+        if !self.allocator.is_active(index)
+        {
+            return Err(value);
+        }
+        Ok(self.column_or_insert::<T>().set(index, value))
+    }
+
+    /// Removes the `T` value of `index`, leaving the rest of the row in place
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8, "one"));
+    ///
+    /// assert_eq!(table.remove::<u8>(index), Some(1));
+    /// assert_eq!(table.remove::<u8>(index), None);
+    /// assert!(table.contains(index));
+    /// ```
+    pub fn remove<T: 'static>(&mut self, index: K) -> Option<T>
+    {
+        self.column_mut::<T>()?.remove(index)
+    }
+
+    /// Returns an immutable reference to the `T` value of `index`, if it has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8,));
+    /// assert_eq!(table.get::<u8>(index), Some(&1));
+    /// assert_eq!(table.get::<u16>(index), None);
+    /// ```
+    pub fn get<T: 'static>(&self, index: K) -> Option<&T>
+    {
+        self.column::<T>()?.get(index)
+    }
+
+    /// Returns a mutable reference to the `T` value of `index`, if it has one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::Table;
+    ///
+    /// let mut table: Table = Table::new();
+    /// let index: Index = table.insert((1u8,));
+    ///
+    /// if let Some(value) = table.get_mut::<u8>(index)
+    /// {
+    ///     *value = 2;
+    /// }
+    /// assert_eq!(table.get::<u8>(index), Some(&2));
+    /// ```
+    pub fn get_mut<T: 'static>(&mut self, index: K) -> Option<&mut T>
+    {
+        self.column_mut::<T>()?.get_mut(index)
+    }
+}
+
+/// A set of values inserted together as one `Table` row, one value per column
+///
+/// Implemented for tuples of up to 8 values of distinct types. `()` inserts an empty row
+pub trait Bundle<K: Key + 'static>
+{
+    /// Sets each value of the bundle in its column at `index`
+    fn set_all(self, table: &mut Table<K>, index: K);
+}
+
+macro_rules! impl_bundle
+{
+    ($($name:ident),*) =>
+    {
+        impl<K: Key + 'static, $($name: 'static),*> Bundle<K> for ($($name,)*)
+        {
+            #[allow(non_snake_case, unused_variables)]
+            fn set_all(self, table: &mut Table<K>, index: K)
+            {
+                let ($($name,)*) = self;
+                $(table.column_or_insert::<$name>().set(index, $name);)*
+            }
+        }
+    };
+}
+
+impl_bundle!();
+impl_bundle!(A);
+impl_bundle!(A, B);
+impl_bundle!(A, B, C);
+impl_bundle!(A, B, C, D);
+impl_bundle!(A, B, C, D, E);
+impl_bundle!(A, B, C, D, E, F);
+impl_bundle!(A, B, C, D, E, F, G);
+impl_bundle!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod table_tests
+{
+    use crate::Index;
+    use crate::closed::Table;
+
+    #[derive(PartialEq, Debug)]
+    struct Position(i32);
+    #[derive(PartialEq, Debug)]
+    struct Health(u32);
+
+    #[test]
+    fn despawn_clears_every_column()
+    {
+        let mut table = Table::new();
+        let index = table.insert((Position(1), Health(10)));
+        let index1 = table.insert((Position(2),));
+        table.add_column::<String>();
+        table.set(index1, String::from("rock")).expect("index1 is active");
+
+        assert!(table.despawn(index1));
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.column::<Position>().map(|column| column.iter().count()), Some(1));
+        assert_eq!(table.column::<String>().map(|column| column.iter().count()), Some(0));
+
+        // The freed index is reused at a new generation without old values
+        let index2 = table.insert(());
+        assert_eq!(index2, Index::from((1, 1)));
+        assert_eq!(table.get::<Position>(index2), None);
+        assert_eq!(table.get::<Health>(index), Some(&Health(10)));
+    }
+
+    #[test]
+    fn stale_index()
+    {
+        let mut table = Table::new();
+        let index = table.insert((Position(1),));
+        table.despawn(index);
+        let index1 = table.insert((Position(2),));
+
+        assert!(!table.despawn(index));
+        assert_eq!(table.set(index, Health(1)), Err(Health(1)));
+        assert_eq!(table.remove::<Position>(index), None);
+        assert_eq!(table.get::<Position>(index1), Some(&Position(2)));
+    }
+
+    #[test]
+    fn debug()
+    {
+        let mut table = Table::new();
+        table.insert((Position(1), 1u8));
+        let debug = format!("{:?}", table);
+        assert!(debug.contains("columns: [\"gen_vec::closed::table::table_tests::Position\", \"u8\"]"));
+    }
+}
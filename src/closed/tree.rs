@@ -0,0 +1,747 @@
+use std::collections::VecDeque;
+use crate::
+{
+    Index,
+    Key,
+    IndexError,
+    closed::{self, ClosedGenVec}
+};
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// A value of a `GenTree` along with the links to its neighbours
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Node<T, K>
+{
+    value: T,
+    parent: Option<K>,
+    first_child: Option<K>,
+    last_child: Option<K>,
+    next_sibling: Option<K>
+}
+
+/// Hierarchy of values stored in a `ClosedGenVec`
+///
+/// Each node links to its parent, its first and last children and its next sibling by key, so every link is checked
+/// against the node's generation when it's followed. A node without a parent is a root. Removing a node
+/// removes its whole subtree, and keys to removed nodes fail like any other stale key
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::closed::GenTree;
+///
+/// let mut tree: GenTree<&str> = GenTree::new();
+/// let world: Index = tree.insert("world");
+/// let player: Index = tree.add_child(world, "player").unwrap();
+/// let sword: Index = tree.add_child(player, "sword").unwrap();
+///
+/// let ancestors: Vec<&str> = tree.ancestors(sword).map(|(_, name)| *name).collect();
+/// assert_eq!(ancestors, vec!["player", "world"]);
+///
+/// assert_eq!(tree.remove_subtree(player), Ok(vec!["player", "sword"]));
+/// assert!(tree.get(sword).is_none());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenTree<T, K = Index>
+{
+    nodes: ClosedGenVec<Node<T, K>, K>
+}
+
+impl<T, K: Key> Default for GenTree<T, K>
+{
+    fn default() -> GenTree<T, K>
+    {
+        GenTree::with_key()
+    }
+}
+
+impl<T> GenTree<T>
+{
+    /// Returns an empty `GenTree`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let tree: GenTree<i32> = GenTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn new() -> GenTree<T>
+    {
+        GenTree::with_key()
+    }
+}
+
+impl<T, K: Key> GenTree<T, K>
+{
+    /// Returns an empty `GenTree` that hands out keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// new_key_type! { struct NodeId; }
+    ///
+    /// let mut tree: GenTree<i32, NodeId> = GenTree::with_key();
+    /// let id: NodeId = tree.insert(0);
+    /// ```
+    pub fn with_key() -> GenTree<T, K>
+    {
+        GenTree { nodes: ClosedGenVec::with_key() }
+    }
+
+    /// Number of nodes in the tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// tree.add_child(root, 1);
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the tree has no nodes
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let tree: GenTree<i32> = GenTree::new();
+    /// assert!(tree.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.nodes.is_empty()
+    }
+
+    /// Returns `true` if `node` is a node of the tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// assert!(tree.contains(root));
+    ///
+    /// tree.remove_subtree(root);
+    /// assert!(!tree.contains(root));
+    /// ```
+    pub fn contains(&self, node: K) -> bool
+    {
+        self.nodes.contains(node)
+    }
+
+    /// Inserts `value` as a new root node
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// assert_eq!(tree.parent(root), None);
+    /// ```
+    pub fn insert(&mut self, value: T) -> K
+    {
+        self.nodes.insert(Node { value, parent: None, first_child: None, last_child: None, next_sibling: None })
+    }
+
+    /// Inserts `value` as the last child of `parent`
+    ///
+    /// Returns `value` back if `parent` isn't a node of the tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// assert_eq!(tree.parent(child), Some(root));
+    ///
+    /// tree.remove_subtree(root);
+    /// assert_eq!(tree.add_child(root, 2), Err(2));
+    /// ```
+    pub fn add_child(&mut self, parent: K, value: T) -> Result<K, T>
+    {
+        if !self.nodes.contains(parent)
+        {
+            return Err(value);
+        }
+
+        let child = self.nodes.insert(Node { value, parent: Some(parent), first_child: None, last_child: None, next_sibling: None });
+        match self.node_mut(parent).last_child.replace(child)
+        {
+            Some(last_child) => self.node_mut(last_child).next_sibling = Some(child),
+            _ => self.node_mut(parent).first_child = Some(child)
+        }
+        Ok(child)
+    }
+
+    /// Unlinks `node` from its parent, making it the root of its own tree along with its descendants
+    ///
+    /// Does nothing if `node` is already a root
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// let grandchild: Index = tree.add_child(child, 2).unwrap();
+    ///
+    /// assert_eq!(tree.detach(child), Ok(()));
+    /// assert_eq!(tree.parent(child), None);
+    /// assert_eq!(tree.children(root).count(), 0);
+    /// assert_eq!(tree.parent(grandchild), Some(child));
+    /// ```
+    pub fn detach(&mut self, node: K) -> Result<(), IndexError>
+    {
+        let (parent, next_sibling) = self.nodes
+                                         .try_get(node)
+                                         .map(|node| (node.parent, node.next_sibling))?;
+        let parent = match parent
+        {
+            Some(parent) => parent,
+            _ => return Ok(())
+        };
+
+        let previous_sibling = self.children(parent)
+                                   .map(|(child, _)| child)
+                                   .take_while(|child| *child != node)
+                                   .last();
+        match previous_sibling
+        {
+            Some(previous_sibling) => self.node_mut(previous_sibling).next_sibling = next_sibling,
+            _ => self.node_mut(parent).first_child = next_sibling
+        }
+        if next_sibling.is_none()
+        {
+            self.node_mut(parent).last_child = previous_sibling;
+        }
+
+        let node = self.node_mut(node);
+        node.parent = None;
+        node.next_sibling = None;
+        Ok(())
+    }
+
+    /// Removes `node` and all of its descendants, returning their values in depth first order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// tree.add_child(child, 2);
+    /// tree.add_child(root, 3);
+    ///
+    /// assert_eq!(tree.remove_subtree(child), Ok(vec![1, 2]));
+    /// assert_eq!(tree.remove_subtree(child), Err(IndexError::Vacant));
+    /// assert_eq!(tree.len(), 2);
+    /// ```
+    pub fn remove_subtree(&mut self, node: K) -> Result<Vec<T>, IndexError>
+    {
+        self.detach(node)?;
+        let subtree: Vec<K> = self.depth_first(node).map(|(node, _)| node).collect();
+        Ok(subtree.into_iter()
+                  .filter_map(|node| self.nodes.remove(node))
+                  .map(|node| node.value)
+                  .collect())
+    }
+
+    /// Returns an immutable reference to the value of `node` if `node` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// assert_eq!(tree.get(root), Some(&0));
+    /// ```
+    pub fn get(&self, node: K) -> Option<&T>
+    {
+        self.try_get(node).ok()
+    }
+
+    /// Returns an immutable reference to the value of `node`, or why `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// tree.remove_subtree(root);
+    /// tree.insert(1);
+    /// assert_eq!(tree.try_get(root), Err(IndexError::Stale { current_generation: 1 }));
+    /// ```
+    pub fn try_get(&self, node: K) -> Result<&T, IndexError>
+    {
+        self.nodes.try_get(node).map(|node| &node.value)
+    }
+
+    /// Returns a mutable reference to the value of `node` if `node` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    ///
+    /// if let Some(value) = tree.get_mut(root)
+    /// {
+    ///     *value = 1;
+    /// }
+    /// assert_eq!(tree.get(root), Some(&1));
+    /// ```
+    pub fn get_mut(&mut self, node: K) -> Option<&mut T>
+    {
+        self.try_get_mut(node).ok()
+    }
+
+    /// Returns a mutable reference to the value of `node`, or why `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// tree.remove_subtree(root);
+    /// assert_eq!(tree.try_get_mut(root), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_get_mut(&mut self, node: K) -> Result<&mut T, IndexError>
+    {
+        self.nodes.try_get_mut(node).map(|node| &mut node.value)
+    }
+
+    /// Returns the parent of `node`, or `None` if `node` is a root or invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// assert_eq!(tree.parent(child), Some(root));
+    /// assert_eq!(tree.parent(root), None);
+    /// ```
+    pub fn parent(&self, node: K) -> Option<K>
+    {
+        self.nodes.get(node)?.parent
+    }
+
+    /// Iterator over every root node of the tree
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// tree.add_child(root, 1);
+    /// tree.insert(2);
+    ///
+    /// let roots: Vec<i32> = tree.roots().map(|(_, value)| *value).collect();
+    /// assert_eq!(roots, vec![0, 2]);
+    /// ```
+    pub fn roots(&self) -> Roots<'_, T, K>
+    {
+        Roots { nodes: self.nodes.iter() }
+    }
+
+    /// Iterator over the children of `node`, in the order they were added
+    ///
+    /// Empty if `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// tree.add_child(root, 1);
+    /// tree.add_child(root, 2);
+    ///
+    /// let children: Vec<i32> = tree.children(root).map(|(_, value)| *value).collect();
+    /// assert_eq!(children, vec![1, 2]);
+    /// ```
+    pub fn children(&self, node: K) -> Children<'_, T, K>
+    {
+        Children { nodes: &self.nodes, next: self.nodes.get(node).and_then(|node| node.first_child) }
+    }
+
+    /// Iterator walking up from the parent of `node` to its root
+    ///
+    /// Empty if `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// let grandchild: Index = tree.add_child(child, 2).unwrap();
+    ///
+    /// let ancestors: Vec<Index> = tree.ancestors(grandchild).map(|(node, _)| node).collect();
+    /// assert_eq!(ancestors, vec![child, root]);
+    /// ```
+    pub fn ancestors(&self, node: K) -> Ancestors<'_, T, K>
+    {
+        Ancestors { nodes: &self.nodes, next: self.parent(node) }
+    }
+
+    /// Iterator over `node` and its descendants, visiting each node before its children
+    ///
+    /// Empty if `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// tree.add_child(child, 2);
+    /// tree.add_child(root, 3);
+    ///
+    /// let values: Vec<i32> = tree.depth_first(root).map(|(_, value)| *value).collect();
+    /// assert_eq!(values, vec![0, 1, 2, 3]);
+    /// ```
+    pub fn depth_first(&self, node: K) -> DepthFirst<'_, T, K>
+    {
+        DepthFirst { nodes: &self.nodes, root: node, next: Some(node).filter(|node| self.contains(*node)) }
+    }
+
+    /// Iterator over `node` and its descendants, visiting each level before the next
+    ///
+    /// Empty if `node` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::closed::GenTree;
+    ///
+    /// let mut tree: GenTree<i32> = GenTree::new();
+    /// let root: Index = tree.insert(0);
+    /// let child: Index = tree.add_child(root, 1).unwrap();
+    /// tree.add_child(child, 2);
+    /// tree.add_child(root, 3);
+    ///
+    /// let values: Vec<i32> = tree.breadth_first(root).map(|(_, value)| *value).collect();
+    /// assert_eq!(values, vec![0, 1, 3, 2]);
+    /// ```
+    pub fn breadth_first(&self, node: K) -> BreadthFirst<'_, T, K>
+    {
+        BreadthFirst { nodes: &self.nodes, queue: Some(node).filter(|node| self.contains(*node)).into_iter().collect() }
+    }
+
+    /// Node of `node`, which is linked from a valid node and so must be valid too
+    fn node_mut(&mut self, node: K) -> &mut Node<T, K>
+    {
+        match self.nodes.get_mut(node)
+        {
+            Some(node) => node,
+            _ => panic!("{:?} is linked but isn't a node of the tree", node)
+        }
+    }
+}
+
+/// Struct for creating an iterator over the root nodes of a `GenTree`
+#[derive(Debug)]
+pub struct Roots<'a, T: 'a, K = Index>
+{
+    nodes: closed::Iter<'a, Node<T, K>, K>
+}
+
+impl<'a, T, K: Key> Iterator for Roots<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        self.nodes
+            .find(|(_, node)| node.parent.is_none())
+            .map(|(index, node)| (index, &node.value))
+    }
+}
+
+/// Struct for creating an iterator over the children of a `GenTree` node
+#[derive(Debug)]
+pub struct Children<'a, T: 'a, K = Index>
+{
+    nodes: &'a ClosedGenVec<Node<T, K>, K>,
+    next: Option<K>
+}
+
+impl<'a, T, K: Key> Iterator for Children<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let index = self.next.take()?;
+        let node = self.nodes.get(index)?;
+        self.next = node.next_sibling;
+        Some((index, &node.value))
+    }
+}
+
+/// Struct for creating an iterator over the ancestors of a `GenTree` node
+#[derive(Debug)]
+pub struct Ancestors<'a, T: 'a, K = Index>
+{
+    nodes: &'a ClosedGenVec<Node<T, K>, K>,
+    next: Option<K>
+}
+
+impl<'a, T, K: Key> Iterator for Ancestors<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let index = self.next.take()?;
+        let node = self.nodes.get(index)?;
+        self.next = node.parent;
+        Some((index, &node.value))
+    }
+}
+
+/// Struct for creating a depth first iterator over a `GenTree` subtree
+///
+/// Follows the tree's links instead of keeping a stack
+#[derive(Debug)]
+pub struct DepthFirst<'a, T: 'a, K = Index>
+{
+    nodes: &'a ClosedGenVec<Node<T, K>, K>,
+    root: K,
+    next: Option<K>
+}
+
+impl<'a, T, K: Key> DepthFirst<'a, T, K>
+{
+    /// Next node after `index` and its descendants, without leaving the subtree of `root`
+    fn successor(&self, mut index: K, mut node: &'a Node<T, K>) -> Option<K>
+    {
+        while index != self.root
+        {
+            if node.next_sibling.is_some()
+            {
+                return node.next_sibling;
+            }
+            index = node.parent?;
+            node = self.nodes.get(index)?;
+        }
+        None
+    }
+}
+
+impl<'a, T, K: Key> Iterator for DepthFirst<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let index = self.next.take()?;
+        let node = self.nodes.get(index)?;
+        self.next = node.first_child.or_else(|| self.successor(index, node));
+        Some((index, &node.value))
+    }
+}
+
+/// Struct for creating a breadth first iterator over a `GenTree` subtree
+#[derive(Debug)]
+pub struct BreadthFirst<'a, T: 'a, K = Index>
+{
+    nodes: &'a ClosedGenVec<Node<T, K>, K>,
+    queue: VecDeque<K>
+}
+
+impl<'a, T, K: Key> Iterator for BreadthFirst<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        loop
+        {
+            let index = self.queue.pop_front()?;
+            if let Some(node) = self.nodes.get(index)
+            {
+                self.queue.extend(Children { nodes: self.nodes, next: node.first_child }.map(|(child, _)| child));
+                return Some((index, &node.value));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tree_tests
+{
+    use crate::{Index, IndexError};
+    use crate::closed::GenTree;
+
+    /// Builds
+    /// ```text
+    /// 0
+    /// ├── 1
+    /// │   ├── 3
+    /// │   └── 4
+    /// └── 2
+    ///     └── 5
+    /// ```
+    fn tree() -> (GenTree<i32>, Vec<Index>)
+    {
+        let mut tree = GenTree::new();
+        let root = tree.insert(0);
+        let one = tree.add_child(root, 1).unwrap();
+        let two = tree.add_child(root, 2).unwrap();
+        let three = tree.add_child(one, 3).unwrap();
+        let four = tree.add_child(one, 4).unwrap();
+        let five = tree.add_child(two, 5).unwrap();
+        (tree, vec![root, one, two, three, four, five])
+    }
+
+    fn values<'a>(iter: impl Iterator<Item = (Index, &'a i32)>) -> Vec<i32>
+    {
+        iter.map(|(_, value)| *value).collect()
+    }
+
+    #[test]
+    fn traversal()
+    {
+        let (tree, nodes) = tree();
+        assert_eq!(values(tree.depth_first(nodes[0])), vec![0, 1, 3, 4, 2, 5]);
+        assert_eq!(values(tree.breadth_first(nodes[0])), vec![0, 1, 2, 3, 4, 5]);
+
+        // Traversal stays within the subtree it started at
+        assert_eq!(values(tree.depth_first(nodes[1])), vec![1, 3, 4]);
+        assert_eq!(values(tree.depth_first(nodes[4])), vec![4]);
+        assert_eq!(values(tree.breadth_first(nodes[2])), vec![2, 5]);
+
+        assert_eq!(values(tree.ancestors(nodes[5])), vec![2, 0]);
+        assert_eq!(values(tree.ancestors(nodes[0])), Vec::<i32>::new());
+        assert_eq!(values(tree.children(nodes[1])), vec![3, 4]);
+    }
+
+    #[test]
+    fn detach()
+    {
+        let (mut tree, nodes) = tree();
+
+        // Detaching a middle, first and last child keeps the remaining siblings linked
+        let six = tree.add_child(nodes[0], 6).unwrap();
+        tree.detach(nodes[2]).unwrap();
+        assert_eq!(values(tree.children(nodes[0])), vec![1, 6]);
+        tree.detach(nodes[1]).unwrap();
+        assert_eq!(values(tree.children(nodes[0])), vec![6]);
+        tree.detach(six).unwrap();
+        assert_eq!(values(tree.children(nodes[0])), Vec::<i32>::new());
+
+        assert_eq!(values(tree.roots()), vec![0, 1, 2, 6]);
+        assert_eq!(values(tree.depth_first(nodes[1])), vec![1, 3, 4]);
+        assert_eq!(tree.detach(nodes[1]), Ok(()));
+        assert_eq!(tree.len(), 7);
+
+        // Children added after detaching the last child follow the remaining ones
+        let seven = tree.add_child(nodes[1], 7).unwrap();
+        tree.detach(seven).unwrap();
+        tree.add_child(nodes[1], 8).unwrap();
+        assert_eq!(values(tree.children(nodes[1])), vec![3, 4, 8]);
+        tree.add_child(nodes[0], 9).unwrap();
+        assert_eq!(values(tree.children(nodes[0])), vec![9]);
+    }
+
+    #[test]
+    fn remove_subtree()
+    {
+        let (mut tree, nodes) = tree();
+        assert_eq!(tree.remove_subtree(nodes[1]), Ok(vec![1, 3, 4]));
+        assert_eq!(tree.len(), 3);
+        assert_eq!(values(tree.children(nodes[0])), vec![2]);
+        assert_eq!(values(tree.depth_first(nodes[0])), vec![0, 2, 5]);
+
+        assert_eq!(tree.remove_subtree(nodes[0]), Ok(vec![0, 2, 5]));
+        assert!(tree.is_empty());
+    }
+
+    #[test]
+    fn stale_links()
+    {
+        let (mut tree, nodes) = tree();
+        tree.remove_subtree(nodes[1]).unwrap();
+
+        // The first freed slot is reused by a newer generation
+        let reused = tree.insert(7);
+        assert_eq!(reused, Index::from((1, 1)));
+
+        assert_eq!(tree.get(nodes[1]), None);
+        assert_eq!(tree.try_get(nodes[1]), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(tree.detach(nodes[1]), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(tree.remove_subtree(nodes[3]), Err(IndexError::Vacant));
+        assert_eq!(tree.add_child(nodes[1], 8), Err(8));
+        assert_eq!(tree.parent(nodes[3]), None);
+        assert_eq!(values(tree.children(nodes[1])), Vec::<i32>::new());
+        assert_eq!(values(tree.ancestors(nodes[4])), Vec::<i32>::new());
+        assert_eq!(values(tree.depth_first(nodes[1])), Vec::<i32>::new());
+        assert_eq!(values(tree.breadth_first(nodes[1])), Vec::<i32>::new());
+
+        assert_eq!(tree.get(reused), Some(&7));
+        assert_eq!(values(tree.depth_first(nodes[0])), vec![0, 2, 5]);
+    }
+}
@@ -0,0 +1,418 @@
+use std::{error, fmt, collections::TryReserveError};
+use crate::{Index, Key, exposed::{IndexAllocator, ExposedGenVec}};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Reason an `Index` could not be used to access a value
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IndexError
+{
+    /// The index points past the end of the vec
+    OutOfBounds,
+    /// The slot the index points to holds no value
+    Vacant,
+    /// The slot has since been reused by a newer generation
+    Stale
+    {
+        /// Generation of the value currently held in the slot
+        current_generation: usize
+    },
+    /// The index is newer than the value held in the slot
+    FromFuture
+}
+
+impl IndexError
+{
+    /// Error for an index of generation `requested` pointing to a value of generation `current`
+    pub(crate) fn mismatch(current: usize, requested: usize) -> IndexError
+    {
+        if current > requested
+        {
+            IndexError::Stale { current_generation: current }
+        }
+        else
+        {
+            IndexError::FromFuture
+        }
+    }
+}
+
+impl fmt::Display for IndexError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            IndexError::OutOfBounds => write!(f, "index is out of bounds"),
+            IndexError::Vacant => write!(f, "index points to a vacant slot"),
+            IndexError::Stale { current_generation } =>
+                write!(f, "index is stale, slot is now at generation {}", current_generation),
+            IndexError::FromFuture => write!(f, "index is from a future generation")
+        }
+    }
+}
+
+impl error::Error for IndexError {}
+
+/// Error returned when a generational vec or `IndexAllocator` has reached its maximum capacity
+///
+/// Holds onto the value that couldn't be inserted so it can be recovered
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct CapacityError<T = ()>
+{
+    value: T,
+    max_capacity: usize,
+    quarantined: usize
+}
+
+impl<T> CapacityError<T>
+{
+    pub(crate) fn new(value: T, max_capacity: usize) -> CapacityError<T>
+    {
+        CapacityError { value, max_capacity, quarantined: 0 }
+    }
+
+    /// Records how many slots were waiting out their quarantine when the capacity was reached
+    pub(crate) fn with_quarantined(self, quarantined: usize) -> CapacityError<T>
+    {
+        CapacityError { quarantined, ..self }
+    }
+
+    /// Swaps the value that couldn't be inserted for `value`
+    pub(crate) fn with_value<U>(self, value: U) -> CapacityError<U>
+    {
+        CapacityError { value, max_capacity: self.max_capacity, quarantined: self.quarantined }
+    }
+
+    /// Returns the value that couldn't be inserted
+    pub fn into_value(self) -> T
+    {
+        self.value
+    }
+
+    /// The maximum capacity that was reached
+    pub fn max_capacity(&self) -> usize
+    {
+        self.max_capacity
+    }
+
+    /// Number of freed slots that still counted against the maximum capacity
+    /// because they were waiting out their quarantine
+    pub fn quarantined(&self) -> usize
+    {
+        self.quarantined
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "maximum capacity of {} reached", self.max_capacity)?;
+        if self.quarantined > 0
+        {
+            write!(f, " with {} freed slots still in quarantine", self.quarantined)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug> error::Error for CapacityError<T> {}
+
+/// Error returned when a value can't be set because its index is older than the slot's generation
+///
+/// Holds onto the value that couldn't be set so it can be recovered
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct SetError<T>
+{
+    value: T,
+    current_generation: usize
+}
+
+impl<T> SetError<T>
+{
+    pub(crate) fn new(value: T, current_generation: usize) -> SetError<T>
+    {
+        SetError { value, current_generation }
+    }
+
+    /// Returns the value that couldn't be set
+    pub fn into_value(self) -> T
+    {
+        self.value
+    }
+
+    /// Generation the slot holds or last held
+    pub fn current_generation(&self) -> usize
+    {
+        self.current_generation
+    }
+}
+
+impl<T> fmt::Display for SetError<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "index is stale, slot is now at generation {}", self.current_generation)
+    }
+}
+
+impl<T: fmt::Debug> error::Error for SetError<T> {}
+
+/// Reason reserving additional space failed
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum ReserveError
+{
+    /// Reserving would grow past the maximum capacity
+    MaxCapacityExceeded
+    {
+        /// The maximum capacity that would have been exceeded
+        max_capacity: usize
+    },
+    /// The memory allocator failed to provide the requested space
+    AllocFailed(TryReserveError)
+}
+
+impl From<TryReserveError> for ReserveError
+{
+    fn from(error: TryReserveError) -> ReserveError
+    {
+        ReserveError::AllocFailed(error)
+    }
+}
+
+impl fmt::Display for ReserveError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            ReserveError::MaxCapacityExceeded { max_capacity } =>
+                write!(f, "reserving would exceed the maximum capacity of {}", max_capacity),
+            ReserveError::AllocFailed(error) => write!(f, "{}", error)
+        }
+    }
+}
+
+impl error::Error for ReserveError {}
+
+/// Reason a value couldn't be moved between two `ExposedGenVec`s
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TransferError
+{
+    /// The source vec has no valid value for the index
+    Source(IndexError),
+    /// The destination vec holds a newer generation at the index's slot
+    DestinationNewer
+    {
+        /// Generation of the value held in the destination
+        current_generation: usize
+    }
+}
+
+impl fmt::Display for TransferError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            TransferError::Source(error) => write!(f, "source {}", error),
+            TransferError::DestinationNewer { current_generation } =>
+                write!(f, "destination holds newer generation {}", current_generation)
+        }
+    }
+}
+
+impl error::Error for TransferError
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)>
+    {
+        match self
+        {
+            TransferError::Source(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+/// Reason a specific `Index` couldn't be allocated
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AllocateAtError
+{
+    /// The index's slot is outside of the allocator's slot range
+    OutOfRange,
+    /// Reaching the index's slot would exceed the allocator's maximum capacity
+    MaxCapacityExceeded
+    {
+        /// The maximum capacity of the allocator
+        max_capacity: usize
+    },
+    /// The index is already active
+    Occupied,
+    /// The index's slot has been freed by a monotonic allocator and can never be reused
+    Retired,
+    /// The index's slot has already reached a newer generation
+    Stale
+    {
+        /// Current generation of the slot
+        current_generation: usize
+    }
+}
+
+impl fmt::Display for AllocateAtError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            AllocateAtError::OutOfRange => write!(f, "index is outside of the allocator's slot range"),
+            AllocateAtError::MaxCapacityExceeded { max_capacity } =>
+                write!(f, "index is past the maximum capacity of {}", max_capacity),
+            AllocateAtError::Occupied => write!(f, "index is already active"),
+            AllocateAtError::Retired => write!(f, "index's slot has been freed and can't be reused"),
+            AllocateAtError::Stale { current_generation } =>
+                write!(f, "index is stale, slot is already at generation {}", current_generation)
+        }
+    }
+}
+
+impl error::Error for AllocateAtError {}
+
+/// Error returned when a value couldn't be inserted at a specific `Index`
+///
+/// Holds onto the value that couldn't be inserted so it can be recovered
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct InsertAtError<T>
+{
+    error: AllocateAtError,
+    value: T
+}
+
+impl<T> InsertAtError<T>
+{
+    pub(crate) fn new(error: AllocateAtError, value: T) -> InsertAtError<T>
+    {
+        InsertAtError { error, value }
+    }
+
+    /// Why the index couldn't be allocated
+    pub fn error(&self) -> AllocateAtError
+    {
+        self.error
+    }
+
+    /// Returns the value that couldn't be inserted
+    pub fn into_value(self) -> T
+    {
+        self.value
+    }
+}
+
+impl<T> fmt::Display for InsertAtError<T>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        self.error.fmt(f)
+    }
+}
+
+impl<T: fmt::Debug> error::Error for InsertAtError<T>
+{
+    fn source(&self) -> Option<&(dyn error::Error + 'static)>
+    {
+        Some(&self.error)
+    }
+}
+
+/// Reason a `Delta` couldn't be applied to a `Mirror`
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeltaError<K = Index>
+{
+    /// One or more deltas were skipped
+    Gap
+    {
+        /// Sequence number the mirror expected
+        expected: u64,
+        /// Sequence number of the delta
+        found: u64
+    },
+    /// The delta has already been applied or is older than the last one applied
+    OutOfOrder
+    {
+        /// Sequence number the mirror expected
+        expected: u64,
+        /// Sequence number of the delta
+        found: u64
+    },
+    /// An inserted index conflicts with the mirror's state, meaning the mirror has diverged
+    Conflict
+    {
+        /// The index that couldn't be inserted
+        index: K,
+        /// Why it couldn't be inserted
+        error: AllocateAtError
+    }
+}
+
+impl<K: Key> fmt::Display for DeltaError<K>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            DeltaError::Gap { expected, found } =>
+                write!(f, "expected delta {} but found delta {}, deltas were skipped", expected, found),
+            DeltaError::OutOfOrder { expected, found } =>
+                write!(f, "expected delta {} but found earlier delta {}", expected, found),
+            DeltaError::Conflict { index, error } => write!(f, "couldn't insert {:?}: {}", index, error)
+        }
+    }
+}
+
+impl<K: Key> error::Error for DeltaError<K> {}
+
+/// Error returned when an `IndexAllocator` and an `ExposedGenVec` don't agree and can't form a `ClosedGenVec`
+///
+/// Holds onto both parts so they can be recovered
+#[derive(Debug)]
+pub struct FromPartsError<T, K = Index>
+{
+    index: K,
+    allocator: Box<IndexAllocator<K>>,
+    vec: ExposedGenVec<T, K>
+}
+
+impl<T, K: Key> FromPartsError<T, K>
+{
+    pub(crate) fn new(index: K, allocator: IndexAllocator<K>, vec: ExposedGenVec<T, K>) -> FromPartsError<T, K>
+    {
+        FromPartsError { index, allocator: Box::new(allocator), vec }
+    }
+
+    /// The first index found holding a value without being active in the allocator
+    pub fn index(&self) -> K
+    {
+        self.index
+    }
+
+    /// Returns the allocator and vec that were rejected
+    pub fn into_parts(self) -> (IndexAllocator<K>, ExposedGenVec<T, K>)
+    {
+        (*self.allocator, self.vec)
+    }
+}
+
+impl<T, K: Key> fmt::Display for FromPartsError<T, K>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{:?} holds a value but isn't active in the allocator", self.index)
+    }
+}
+
+impl<T: fmt::Debug, K: Key> error::Error for FromPartsError<T, K> {}
@@ -2,20 +2,94 @@ use std::
 {
     vec,
     vec::Vec,
-    iter,
-    slice
+    mem,
+    marker::PhantomData,
+    collections::TryReserveError
 };
-use crate::{Index, Item};
+use crate::{Index, Key, IndexError, Item, OccupancyBits, Ones, SetError, TransferError, exposed::IndexAllocator};
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
 /// Generationally indexed vector that relies on an independent `IndexAllocator`
-#[derive(Default, Debug)]
+///
+/// Values are accessed with keys of type `K`, which is `Index` unless another `Key` is given
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExposedGenVec<T, K = Index>
+{
+    items: Vec<Slot<T>>,
+    occupied: OccupancyBits,
+    key_type: PhantomData<K>
+}
+
+/// A slot within an `ExposedGenVec`
+///
+/// Vacant slots remember the last generation they held so stale indices stay rejected after removal
+#[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct ExposedGenVec<T>
+pub(crate) enum Slot<T>
+{
+    /// The slot holds a value
+    Occupied(Item<T>),
+    /// The slot holds no value
+    Vacant
+    {
+        /// Generation of the last value held in the slot, or 0 if it never held one
+        generation: usize
+    }
+}
+
+impl<T> Slot<T>
+{
+    /// Generation of the value held in the slot, or of the last value it held
+    pub(crate) fn generation(&self) -> usize
+    {
+        match self
+        {
+            Slot::Occupied(item) => item.generation,
+            Slot::Vacant { generation } => *generation
+        }
+    }
+
+    /// The slot's item, if it holds one
+    pub(crate) fn item(&self) -> Option<&Item<T>>
+    {
+        match self
+        {
+            Slot::Occupied(item) => Some(item),
+            _ => None
+        }
+    }
+
+    /// The slot's item mutably, if it holds one
+    pub(crate) fn item_mut(&mut self) -> Option<&mut Item<T>>
+    {
+        match self
+        {
+            Slot::Occupied(item) => Some(item),
+            _ => None
+        }
+    }
+
+    /// Error for an index of generation `requested` that doesn't match the slot
+    fn mismatch(&self, requested: usize) -> IndexError
+    {
+        match self
+        {
+            Slot::Occupied(item) => IndexError::mismatch(item.generation, requested),
+            Slot::Vacant { generation } if *generation > requested => IndexError::Stale { current_generation: *generation },
+            Slot::Vacant { .. } => IndexError::Vacant
+        }
+    }
+}
+
+impl<T, K: Key> Default for ExposedGenVec<T, K>
 {
-    items: Vec<Option<Item<T>>>
+    fn default() -> ExposedGenVec<T, K>
+    {
+        ExposedGenVec::with_key()
+    }
 }
 
 impl<T> ExposedGenVec<T>
@@ -30,10 +104,7 @@ impl<T> ExposedGenVec<T>
     /// ```
     pub fn new() -> ExposedGenVec<T>
     {
-        ExposedGenVec
-        {
-            items: Vec::new()
-        }
+        ExposedGenVec::with_key()
     }
 
     /// Returns a `ExposedGenVec` with initial capacity of `capacity`
@@ -48,10 +119,55 @@ impl<T> ExposedGenVec<T>
     /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::with_capacity(5);
     /// ```
     pub fn with_capacity(capacity: usize) -> ExposedGenVec<T>
+    {
+        ExposedGenVec::with_capacity_and_key(capacity)
+    }
+}
+
+impl<T, K: Key> ExposedGenVec<T, K>
+{
+    /// Returns an empty `ExposedGenVec` accessed with keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::exposed::ExposedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let vec: ExposedGenVec<i32, EntityId> = ExposedGenVec::with_key();
+    /// ```
+    pub fn with_key() -> ExposedGenVec<T, K>
     {
         ExposedGenVec
         {
-            items: Vec::with_capacity(capacity)
+            items: Vec::new(),
+            occupied: OccupancyBits::new(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Returns a `ExposedGenVec` with initial capacity of `capacity` accessed with keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::exposed::ExposedGenVec;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let vec: ExposedGenVec<i32, EntityId> = ExposedGenVec::with_capacity_and_key(5);
+    /// assert_eq!(vec.capacity(), 5);
+    /// ```
+    pub fn with_capacity_and_key(capacity: usize) -> ExposedGenVec<T, K>
+    {
+        ExposedGenVec
+        {
+            items: Vec::with_capacity(capacity),
+            occupied: OccupancyBits::new(),
+            key_type: PhantomData
         }
     }
 
@@ -96,6 +212,43 @@ impl<T> ExposedGenVec<T>
         self.items.reserve(additional)
     }
 
+    /// Reserves extra space for *at least* `additional` more elements,
+    /// returning an error instead of aborting if the space can't be reserved
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::ExposedGenVec;
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert!(vec.try_reserve(4).is_ok());
+    /// assert!(vec.capacity() >= 4);
+    ///
+    /// assert!(vec.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError>
+    {
+        self.items.try_reserve(additional)
+    }
+
+    /// Approximate number of heap bytes used to store the vec's items
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::ExposedGenVec;
+    ///
+    /// let vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert_eq!(vec.heap_bytes(), 0);
+    ///
+    /// let vec: ExposedGenVec<i32> = ExposedGenVec::with_capacity(5);
+    /// assert!(vec.heap_bytes() >= 5 * std::mem::size_of::<i32>());
+    /// ```
+    pub fn heap_bytes(&self) -> usize
+    {
+        self.items.capacity() * mem::size_of::<Slot<T>>() + self.occupied.heap_bytes()
+    }
+
     /// Returns `true` if the `index` points to a valid item
     ///
     /// # Examples
@@ -112,14 +265,16 @@ impl<T> ExposedGenVec<T>
     /// vec.set(index, 0);
     /// assert!(vec.contains(index));
     /// ```
-    pub fn contains(&self, index: Index) -> bool
+    pub fn contains(&self, index: K) -> bool
     {
         self.get(index).is_some()
     }
 
     /// Set the value for the given `index` and returns the previous value (if any)
     ///
-    /// This may overwrite past (but not future) generations
+    /// This may overwrite past (but not future) generations. If the slot already holds or held a newer
+    /// generation, `index` is stale: `value` is silently dropped and `None` is returned, just like setting
+    /// an empty slot. Use `try_set` to tell the two apart and get `value` back
     ///
     /// # Examples
     ///
@@ -136,35 +291,67 @@ impl<T> ExposedGenVec<T>
     ///
     /// let replaced: i32 = vec.set(index, 1).expect("0");
     /// assert_eq!(replaced, 0);
+    ///
+    /// allocator.deallocate(index);
+    /// let index1: Index = allocator.allocate();
+    /// vec.set(index1, 2);
+    ///
+    /// // The stale index's value is dropped
+    /// assert_eq!(vec.set(index, 3), None);
+    /// assert_eq!(vec.get(index1), Some(&2));
     /// ```
-    pub fn set(&mut self, index: Index, value: T) -> Option<T>
+    pub fn set(&mut self, index: K, value: T) -> Option<T>
     {
-        // If vec is smaller than the index, resize it and fill intermittent indices with None
+        self.try_set(index, value).unwrap_or(None)
+    }
+
+    /// Set the value for the given `index` and returns the previous value (if any),
+    /// or hands `value` back if the slot already holds or held a newer generation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert_eq!(vec.try_set(index, 0), Ok(None));
+    ///
+    /// allocator.deallocate(index);
+    /// let index1: Index = allocator.allocate();
+    /// assert_eq!(vec.try_set(index1, 1), Ok(Some(0)));
+    /// vec.remove(index1);
+    ///
+    /// let error = vec.try_set(index, 2).expect_err("index is stale");
+    /// assert_eq!(error.current_generation(), 1);
+    /// assert_eq!(error.into_value(), 2);
+    /// ```
+    pub fn try_set(&mut self, index: K, value: T) -> Result<Option<T>, SetError<T>>
+    {
+        let index = Index::from_key(index);
+        // If vec is smaller than the index, resize it and fill intermittent indices with vacant slots
         if self.items.len() < index.index + 1
         {
-            self.items.resize_with(index.index + 1, | | None);
+            self.items.resize_with(index.index + 1, | | Slot::Vacant { generation: 0 });
         }
 
-        match self.items.get_mut(index.index)
+        let slot = &mut self.items[index.index];
+        if slot.generation() > index.generation
         {
-            Some(Some(item)) if item.generation <= index.generation =>
-                {
-                    match std::mem::replace(&mut self.items[index.index], Some(Item { value, generation: index.generation }))
-                    {
-                        Some(item) => Some(item.value),
-                        _ => None
-                    }
-                },
-            Some(_) =>
-                {
-                    self.items[index.index] = Some(Item { value, generation: index.generation });
-                    None
-                }
-            _ => panic!(format!("Index is out of bounds despite internal vec being resized: {:?}", index))
+            return Err(SetError::new(value, slot.generation()));
+        }
+        self.occupied.insert(index.index);
+        match mem::replace(slot, Slot::Occupied(Item { value, generation: index.generation }))
+        {
+            Slot::Occupied(item) => Ok(Some(item.value)),
+            _ => Ok(None)
         }
     }
 
-    /// Removes the value of `index` from the vec and internally sets the element to `None`
+    /// Removes the value of `index` from the vec, leaving its slot vacant at the same generation
     ///
     /// # Examples
     ///
@@ -180,17 +367,179 @@ impl<T> ExposedGenVec<T>
     ///
     /// assert_eq!(replaced, Some(0));
     /// ```
-    pub fn remove(&mut self, index: Index) -> Option<T>
+    pub fn remove(&mut self, index: K) -> Option<T>
     {
-        match self.items.get(index.index)
+        self.try_remove(index).ok()
+    }
+
+    /// Removes the value of `index` from the vec, or returns why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert_eq!(vec.try_remove(index), Err(IndexError::OutOfBounds));
+    ///
+    /// vec.set(index, 0);
+    /// assert_eq!(vec.try_remove(index), Ok(0));
+    /// assert_eq!(vec.try_remove(index), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_remove(&mut self, index: K) -> Result<T, IndexError>
+    {
+        let index = Index::from_key(index);
+        match self.items.get_mut(index.index)
         {
-            Some(Some(item)) if index.generation == item.generation =>
+            Some(Slot::Occupied(item)) if item.generation == index.generation =>
                 {
-                    let removed = std::mem::replace(&mut self.items[index.index], None)
-                                            .expect(format!("{:?} shouldn't access a None", index).as_str());
-                    Some(removed.value)
+                    let vacant = Slot::Vacant { generation: item.generation };
+                    self.occupied.remove(index.index);
+                    match mem::replace(&mut self.items[index.index], vacant)
+                    {
+                        Slot::Occupied(item) => Ok(item.value),
+                        _ => panic!("{:?} shouldn't access a vacant slot", index)
+                    }
                 },
-            _ => None
+            Some(slot) => Err(slot.mismatch(index.generation)),
+            None => Err(IndexError::OutOfBounds)
+        }
+    }
+
+    /// Moves the value of `index` into `to` without reallocating `index`,
+    /// returning the value `to` previously held at `index` (if any)
+    ///
+    /// Fails, leaving both vecs untouched, if `index` isn't valid in this vec or
+    /// if `to` holds a newer generation at the same slot
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut inactive: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// let mut active: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// inactive.set(index, 5);
+    ///
+    /// assert_eq!(inactive.transfer(&mut active, index), Ok(None));
+    /// assert!(!inactive.contains(index));
+    /// assert_eq!(active.get(index), Some(&5));
+    /// ```
+    pub fn transfer(&mut self, to: &mut ExposedGenVec<T, K>, index: K) -> Result<Option<T>, TransferError>
+    {
+        self.try_get(index).map_err(TransferError::Source)?;
+        let (slot, generation) = index.into_raw_parts();
+        if let Some(slot) = to.items.get(slot)
+        {
+            if slot.generation() > generation
+            {
+                return Err(TransferError::DestinationNewer { current_generation: slot.generation() });
+            }
+        }
+
+        let value = self.try_remove(index).map_err(TransferError::Source)?;
+        Ok(to.set(index, value))
+    }
+
+    /// Moves the values of every index in `indices` into `to` without reallocating them
+    ///
+    /// Returns the result of each transfer in order. See `transfer`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError, TransferError};
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut inactive: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// let mut active: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// inactive.set(index, 5);
+    ///
+    /// let results = inactive.transfer_many(&mut active, vec![index, index1]);
+    /// assert_eq!(results, vec![Ok(None), Err(TransferError::Source(IndexError::OutOfBounds))]);
+    /// assert_eq!(active.get(index), Some(&5));
+    /// ```
+    pub fn transfer_many<I>(&mut self, to: &mut ExposedGenVec<T, K>, indices: I) -> Vec<Result<Option<T>, TransferError>>
+        where I: IntoIterator<Item = K>
+    {
+        indices.into_iter().map(|index| self.transfer(to, index)).collect()
+    }
+
+    /// Exchanges the values of `a` and `b` in place, leaving both indices pointing to their slots
+    ///
+    /// Fails, leaving the vec untouched, with the reason the first invalid index couldn't be used
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let a: Index = allocator.allocate();
+    /// let b: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// vec.set(a, 0);
+    /// vec.set(b, 1);
+    ///
+    /// assert_eq!(vec.swap(a, b), Ok(()));
+    /// assert_eq!(vec.get(a), Some(&1));
+    /// assert_eq!(vec.get(b), Some(&0));
+    /// ```
+    pub fn swap(&mut self, a: K, b: K) -> Result<(), IndexError>
+    {
+        self.try_get(a)?;
+        self.try_get(b)?;
+
+        let (a, b) = (Index::from_key(a).index, Index::from_key(b).index);
+        let (low, high) = (a.min(b), a.max(b));
+        let (left, right) = self.items.split_at_mut(high);
+        if let (Some(Slot::Occupied(low)), Some(Slot::Occupied(high))) = (left.get_mut(low), right.first_mut())
+        {
+            mem::swap(&mut low.value, &mut high.value);
+        }
+        Ok(())
+    }
+
+    /// Replaces the value of a valid `index`, returning the previous value
+    ///
+    /// Unlike `set`, this never fills a vacant slot. If `index` is invalid, `value` is handed back as the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert_eq!(vec.replace(index, 1), Err(1));
+    ///
+    /// vec.set(index, 0);
+    /// assert_eq!(vec.replace(index, 1), Ok(0));
+    /// assert_eq!(vec.get(index), Some(&1));
+    /// ```
+    pub fn replace(&mut self, index: K, value: T) -> Result<T, T>
+    {
+        match self.get_mut(index)
+        {
+            Some(current) => Ok(mem::replace(current, value)),
+            _ => Err(value)
         }
     }
 
@@ -210,12 +559,41 @@ impl<T> ExposedGenVec<T>
     /// let value: Option<&i32> = vec.get(index);
     /// assert_eq!(value, Some(&0));
     /// ```
-    pub fn get(&self, index: Index) -> Option<&T>
+    pub fn get(&self, index: K) -> Option<&T>
     {
+        self.try_get(index).ok()
+    }
+
+    /// Returns an immutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// assert_eq!(vec.try_get(index), Err(IndexError::OutOfBounds));
+    ///
+    /// vec.set(index, 0);
+    /// assert_eq!(vec.try_get(index), Ok(&0));
+    ///
+    /// allocator.deallocate(index);
+    /// let index1: Index = allocator.allocate();
+    /// vec.set(index1, 1);
+    /// assert_eq!(vec.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+    /// ```
+    pub fn try_get(&self, index: K) -> Result<&T, IndexError>
+    {
+        let index = Index::from_key(index);
         match self.items.get(index.index)
         {
-            Some(Some(item)) if item.generation == index.generation => Some(&item.value),
-            _ => None
+            Some(Slot::Occupied(item)) if item.generation == index.generation => Ok(&item.value),
+            Some(slot) => Err(slot.mismatch(index.generation)),
+            None => Err(IndexError::OutOfBounds)
         }
     }
 
@@ -244,15 +622,112 @@ impl<T> ExposedGenVec<T>
     /// let value: Option<&i32> = vec.get(index);
     /// assert_eq!(value, Some(&1));
     /// ```
-    pub fn get_mut(&mut self, index: Index) -> Option<&mut T>
+    pub fn get_mut(&mut self, index: K) -> Option<&mut T>
     {
+        self.try_get_mut(index).ok()
+    }
+
+    /// Returns a mutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// vec.set(index, 0);
+    ///
+    /// if let Ok(value) = vec.try_get_mut(index)
+    /// {
+    ///     *value = 1;
+    /// }
+    /// assert_eq!(vec.get(index), Some(&1));
+    ///
+    /// vec.remove(index);
+    /// assert_eq!(vec.try_get_mut(index), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_get_mut(&mut self, index: K) -> Result<&mut T, IndexError>
+    {
+        let index = Index::from_key(index);
         match self.items.get_mut(index.index)
         {
-            Some(Some(item)) if item.generation == index.generation => Some(&mut item.value),
-            _ => None
+            Some(slot) if slot.generation() != index.generation => Err(slot.mismatch(index.generation)),
+            Some(slot) => slot.item_mut().map(|item| &mut item.value).ok_or(IndexError::Vacant),
+            None => Err(IndexError::OutOfBounds)
         }
     }
 
+    /// Returns an immutable reference to the value of `index` if `index` is valid here and still active in `allocator`
+    ///
+    /// Guards against reading values left behind by indices that were deallocated but never removed from the vec
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// vec.set(index, 0);
+    /// assert_eq!(vec.get_live(&allocator, index), Some(&0));
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(vec.get(index), Some(&0));
+    /// assert_eq!(vec.get_live(&allocator, index), None);
+    /// ```
+    pub fn get_live(&self, allocator: &IndexAllocator<K>, index: K) -> Option<&T>
+    {
+        if allocator.is_active(index)
+        {
+            self.get(index)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Drops every value whose index is no longer active in `allocator`, returning how many were dropped
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// vec.set(index, 0);
+    /// vec.set(index1, 1);
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(vec.retain_live(&allocator), 1);
+    /// assert!(!vec.contains(index));
+    /// assert!(vec.contains(index1));
+    /// ```
+    pub fn retain_live(&mut self, allocator: &IndexAllocator<K>) -> usize
+    {
+        let dead: Vec<K> = self.iter()
+                               .map(|(index, _)| index)
+                               .filter(|index| !allocator.is_active(*index))
+                               .collect();
+        for index in &dead
+        {
+            self.remove(*index);
+        }
+        dead.len()
+    }
+
     /// Returns an iterator of immutable references to the vec elements
     ///
     /// Each iterator step returns (Index, &T)
@@ -280,11 +755,13 @@ impl<T> ExposedGenVec<T>
     ///     println!("Index: {:?}, Value: {}", index, value);
     /// }
     /// ```
-    pub fn iter(&self) -> Iter<T>
+    pub fn iter(&self) -> Iter<'_, T, K>
     {
         Iter
         {
-            internal: self.items.iter().enumerate()
+            items: &self.items,
+            occupied: self.occupied.iter(),
+            key_type: PhantomData
         }
     }
 
@@ -309,83 +786,197 @@ impl<T> ExposedGenVec<T>
     ///     *value = 30;
     /// }
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<T>
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K>
     {
         IterMut
         {
-            internal: self.items.iter_mut().enumerate()
+            items: &mut self.items,
+            offset: 0,
+            occupied: self.occupied.iter(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Consumes the vec, converting every value with `f` into a vec of `U`
+    ///
+    /// Every value keeps its slot and generation, so indices into this vec stay valid in the new one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// vec.set(index, 2);
+    ///
+    /// let names: ExposedGenVec<String> = vec.map(|_, value| format!("#{}", value));
+    /// assert_eq!(names.get(index).map(String::as_str), Some("#2"));
+    /// ```
+    pub fn map<U, F>(self, mut f: F) -> ExposedGenVec<U, K>
+        where F: FnMut(K, T) -> U
+    {
+        let items = self.items
+                        .into_iter()
+                        .enumerate()
+                        .map(|(slot, item)| match item
+                        {
+                            Slot::Occupied(Item { value, generation }) =>
+                                Slot::Occupied(Item { value: f(K::from_raw_parts(slot, generation), value), generation }),
+                            Slot::Vacant { generation } => Slot::Vacant { generation }
+                        })
+                        .collect();
+        ExposedGenVec
+        {
+            items,
+            occupied: self.occupied,
+            key_type: PhantomData
         }
     }
+
+    /// Returns the bitset of the vec's occupied slots
+    ///
+    /// Slots holding a value of any generation are occupied. Intersect the bitsets of several vecs
+    /// to find the slots they all hold values for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut a: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// let mut b: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// a.set(index, 0);
+    /// a.set(index1, 1);
+    /// b.set(index1, 1);
+    ///
+    /// let mut both = a.occupied_bits().clone();
+    /// both.intersect_with(b.occupied_bits());
+    /// assert_eq!(both.iter().collect::<Vec<usize>>(), vec![1]);
+    /// ```
+    pub fn occupied_bits(&self) -> &OccupancyBits
+    {
+        &self.occupied
+    }
+
+    /// Returns a vec made of `items`, which are numbered by slot
+    pub(crate) fn from_slots(items: Vec<Slot<T>>) -> ExposedGenVec<T, K>
+    {
+        let mut occupied = OccupancyBits::new();
+        for (slot, item) in items.iter().enumerate()
+        {
+            if item.item().is_some()
+            {
+                occupied.insert(slot);
+            }
+        }
+        ExposedGenVec
+        {
+            items,
+            occupied,
+            key_type: PhantomData
+        }
+    }
+
+    /// Consumes the vec into its slots, numbered by slot
+    pub(crate) fn into_slots(self) -> Vec<Slot<T>>
+    {
+        self.items
+    }
+
+    /// Every slot of the vec, including vacant ones
+    pub(crate) fn slots(&self) -> &[Slot<T>]
+    {
+        &self.items
+    }
+
+    /// Every slot of the vec mutably, including vacant ones, along with which are occupied
+    pub(crate) fn slots_mut(&mut self) -> (&mut [Slot<T>], &OccupancyBits)
+    {
+        (&mut self.items, &self.occupied)
+    }
 }
 
 /// Struct for consuming a `ExposedGenVec` into an iterator
 #[derive(Debug)]
-pub struct IntoIter<T>
+pub struct IntoIter<T, K = Index>
 {
-    internal: iter::Enumerate<vec::IntoIter<Option<Item<T>>>>
+    items: vec::IntoIter<Slot<T>>,
+    next: usize,
+    occupied: OccupancyBits,
+    key_type: PhantomData<K>
 }
 
-impl<T> Iterator for IntoIter<T>
+impl<T, K: Key> Iterator for IntoIter<T, K>
 {
-    type Item = (Index, T);
+    type Item = (K, T);
 
     fn next(&mut self) -> Option<Self::Item>
     {
-        loop
+        let slot = self.occupied.next_occupied(self.next)?;
+        let item = self.items.nth(slot - self.next)?;
+        self.next = slot + 1;
+        match item
         {
-            match self.internal.next()
-            {
-                Some((_, None)) => { continue; },
-                Some((index, Some(item))) => return Some((Index { index, generation: item.generation}, item.value)),
-                _ => return None
-            };
+            Slot::Occupied(item) => Some((K::from_raw_parts(slot, item.generation), item.value)),
+            _ => panic!("Occupied slot {} shouldn't be vacant", slot)
         }
     }
 }
 
-impl<T> IntoIterator for ExposedGenVec<T>
+impl<T, K: Key> IntoIterator for ExposedGenVec<T, K>
 {
-    type Item = (Index, T);
-    type IntoIter = IntoIter<T>;
+    type Item = (K, T);
+    type IntoIter = IntoIter<T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
         IntoIter
         {
-            internal: self.items.into_iter().enumerate()
+            items: self.items.into_iter(),
+            next: 0,
+            occupied: self.occupied,
+            key_type: PhantomData
         }
     }
 }
 
 /// Struct for creating an iterator over an immutable `ExposedGenVec` reference
 #[derive(Debug)]
-pub struct Iter<'a, T: 'a>
+pub struct Iter<'a, T: 'a, K = Index>
 {
-    internal: iter::Enumerate<slice::Iter<'a, Option<Item<T>>>>
+    items: &'a [Slot<T>],
+    occupied: Ones<'a>,
+    key_type: PhantomData<K>
 }
 
-impl<'a, T> Iterator for Iter<'a, T>
+impl<'a, T, K: Key> Iterator for Iter<'a, T, K>
 {
-    type Item = (Index, &'a T);
+    type Item = (K, &'a T);
 
     fn next(&mut self) -> Option<Self::Item>
     {
-        loop
+        let slot = self.occupied.next()?;
+        match &self.items[slot]
         {
-            match self.internal.next()
-            {
-                Some((_, None)) => { continue; },
-                Some((index, Some(item))) => return Some((Index { index, generation: item.generation}, &item.value)),
-                _ => return None
-            };
+            Slot::Occupied(item) => Some((K::from_raw_parts(slot, item.generation), &item.value)),
+            _ => panic!("Occupied slot {} shouldn't be vacant", slot)
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a ExposedGenVec<T>
+impl<'a, T, K: Key> IntoIterator for &'a ExposedGenVec<T, K>
 {
-    type Item = (Index, &'a T);
-    type IntoIter = Iter<'a, T>;
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
@@ -395,33 +986,38 @@ impl<'a, T> IntoIterator for &'a ExposedGenVec<T>
 
 /// Struct for creating an iterator over a mutable `ExposedGenVec` reference
 #[derive(Debug)]
-pub struct IterMut<'a, T: 'a>
+pub struct IterMut<'a, T: 'a, K = Index>
 {
-    internal: iter::Enumerate<slice::IterMut<'a, Option<Item<T>>>>
+    items: &'a mut [Slot<T>],
+    offset: usize,
+    occupied: Ones<'a>,
+    key_type: PhantomData<K>
 }
 
-impl<'a, T: 'a> Iterator for IterMut<'a, T>
+impl<'a, T: 'a, K: Key> Iterator for IterMut<'a, T, K>
 {
-    type Item = (Index, &'a mut T);
+    type Item = (K, &'a mut T);
 
     fn next(&mut self) -> Option<Self::Item>
     {
-        loop
+        let slot = self.occupied.next()?;
+        // Split off every slot up to and including `slot`, since later steps only look further ahead
+        let items = mem::take(&mut self.items);
+        let (visited, rest) = items.split_at_mut(slot - self.offset + 1);
+        self.items = rest;
+        self.offset = slot + 1;
+        match visited.last_mut()
         {
-            match self.internal.next()
-            {
-                Some((_, None)) => { continue; },
-                Some((index, Some(item))) => return Some((Index { index, generation: item.generation}, &mut item.value)),
-                _ => return None
-            };
+            Some(Slot::Occupied(item)) => Some((K::from_raw_parts(slot, item.generation), &mut item.value)),
+            _ => panic!("Occupied slot {} shouldn't be vacant", slot)
         }
     }
 }
 
-impl<'a, T> IntoIterator for &'a mut ExposedGenVec<T>
+impl<'a, T, K: Key> IntoIterator for &'a mut ExposedGenVec<T, K>
 {
-    type Item = (Index, &'a mut T);
-    type IntoIter = IterMut<'a, T>;
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
@@ -429,21 +1025,21 @@ impl<'a, T> IntoIterator for &'a mut ExposedGenVec<T>
     }
 }
 
-impl<T> std::ops::Index<Index> for ExposedGenVec<T>
+impl<T, K: Key> std::ops::Index<K> for ExposedGenVec<T, K>
 {
     type Output = T;
 
-    fn index(&self, index: Index) -> &Self::Output
+    fn index(&self, index: K) -> &Self::Output
     {
-        self.get(index).expect(format!("Index should be valid: {:?}", index).as_str())
+        self.try_get(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
     }
 }
 
-impl<T> std::ops::IndexMut<Index> for ExposedGenVec<T>
+impl<T, K: Key> std::ops::IndexMut<K> for ExposedGenVec<T, K>
 {
-    fn index_mut(&mut self, index: Index) -> &mut Self::Output
+    fn index_mut(&mut self, index: K) -> &mut Self::Output
     {
-        self.get_mut(index).expect(format!("Index should be valid: {:?}", index).as_str())
+        self.try_get_mut(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
     }
 }
 
@@ -451,6 +1047,7 @@ impl<T> std::ops::IndexMut<Index> for ExposedGenVec<T>
 mod vec_tests
 {
     use crate::exposed::*;
+    use crate::{Index, IndexError, TransferError};
 
     #[test]
     fn capacity()
@@ -520,6 +1117,128 @@ mod vec_tests
         assert_eq!(value, Some(&2));
     }
 
+    #[test]
+    fn try_get()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+
+        let mut vec = ExposedGenVec::new();
+        assert_eq!(vec.try_get(index), Err(IndexError::OutOfBounds));
+
+        vec.set(index1, 1);
+        assert_eq!(vec.try_get(index), Err(IndexError::Vacant));
+        assert_eq!(vec.try_get(index1), Ok(&1));
+
+        allocator.deallocate(index1);
+        let index2 = allocator.allocate();
+        assert_eq!(vec.try_get(index2), Err(IndexError::FromFuture));
+
+        vec.set(index2, 2);
+        assert_eq!(vec.try_get(index1), Err(IndexError::Stale { current_generation: 1 }));
+
+        if let Ok(value) = vec.try_get_mut(index2)
+        {
+            *value = 3;
+        }
+        assert_eq!(vec.try_get_mut(index1), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(vec.get(index2), Some(&3));
+    }
+
+    #[test]
+    fn try_remove()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut vec = ExposedGenVec::new();
+        assert_eq!(vec.try_remove(index), Err(IndexError::OutOfBounds));
+
+        vec.set(index, 0);
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+        assert_eq!(vec.try_remove(index1), Err(IndexError::FromFuture));
+
+        assert_eq!(vec.try_remove(index), Ok(0));
+        assert_eq!(vec.try_remove(index), Err(IndexError::Vacant));
+    }
+
+    #[test]
+    fn vacant_keeps_generation()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+
+        let mut vec = ExposedGenVec::new();
+        vec.set(index1, 1);
+        assert_eq!(vec.remove(index1), Some(1));
+
+        assert_eq!(vec.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(vec.try_remove(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(vec.set(index, 0), None);
+        assert!(!vec.contains(index));
+        assert_eq!(vec.try_get(index1), Err(IndexError::Vacant));
+
+        let error = vec.try_set(index, 0).expect_err("index is stale");
+        assert_eq!(error.current_generation(), 1);
+        assert_eq!(vec.try_set(index1, 2), Ok(None));
+        assert_eq!(vec.iter().collect::<Vec<(Index, &i32)>>(), vec![(index1, &2)]);
+    }
+
+    #[test]
+    fn transfer()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut from = ExposedGenVec::new();
+        let mut to = ExposedGenVec::new();
+        assert_eq!(from.transfer(&mut to, index), Err(TransferError::Source(IndexError::OutOfBounds)));
+
+        from.set(index, 0);
+        to.set(index, 1);
+        assert_eq!(from.transfer(&mut to, index), Ok(Some(1)));
+        assert!(!from.contains(index));
+        assert_eq!(to.get(index), Some(&0));
+
+        // Destination has moved on to a newer generation
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+        to.set(index1, 2);
+        from.set(index, 3);
+        assert_eq!(from.transfer(&mut to, index), Err(TransferError::DestinationNewer { current_generation: 1 }));
+        assert_eq!(from.get(index), Some(&3));
+        assert_eq!(to.get(index1), Some(&2));
+
+        // Source is stale
+        from.set(index1, 4);
+        assert_eq!(from.transfer(&mut to, index), Err(TransferError::Source(IndexError::Stale { current_generation: 1 })));
+        assert_eq!(from.transfer(&mut to, index1), Ok(Some(2)));
+        assert_eq!(to.get(index1), Some(&4));
+    }
+
+    #[test]
+    fn transfer_many()
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+
+        let mut from = ExposedGenVec::new();
+        let mut to = ExposedGenVec::new();
+        for (i, index) in indices.iter().enumerate().skip(1)
+        {
+            from.set(*index, i);
+        }
+
+        let results = from.transfer_many(&mut to, indices.iter().copied());
+        assert_eq!(results, vec![Err(TransferError::Source(IndexError::Vacant)), Ok(None), Ok(None), Ok(None)]);
+        assert_eq!(from.iter().count(), 0);
+        assert_eq!(to.iter().map(|(_, value)| *value).collect::<Vec<usize>>(), vec![1, 2, 3]);
+    }
+
     #[test]
     fn iter()
     {
@@ -596,4 +1315,102 @@ mod vec_tests
         let value = vec.get(index);
         assert_eq!(value, Some(&5));
     }
-}
\ No newline at end of file
+
+    #[test]
+    #[should_panic(expected = "index points to a vacant slot")]
+    fn index_vacant()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut vec = ExposedGenVec::<i32>::new();
+        vec.set(index, 3);
+        vec.remove(index);
+
+        let _ = vec[index];
+    }
+
+    #[test]
+    fn swap()
+    {
+        let mut allocator = IndexAllocator::new();
+        let a = allocator.allocate();
+        let b = allocator.allocate();
+
+        let mut vec = ExposedGenVec::<i32>::new();
+        vec.set(a, 0);
+        assert_eq!(vec.swap(a, b), Err(IndexError::OutOfBounds));
+
+        vec.set(b, 1);
+        assert_eq!(vec.swap(b, a), Ok(()));
+        assert_eq!(vec.swap(a, a), Ok(()));
+        assert_eq!(vec.get(a), Some(&1));
+        assert_eq!(vec.get(b), Some(&0));
+    }
+
+    #[test]
+    fn map()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+        allocator.deallocate(index);
+        let index2 = allocator.allocate();
+
+        let mut vec = ExposedGenVec::<i32>::new();
+        vec.set(index1, 1);
+        vec.set(index2, 2);
+
+        let vec = vec.map(|index, value| (index, value * 10));
+        assert_eq!(vec.get(index), None);
+        assert_eq!(vec.get(index1), Some(&(index1, 10)));
+        assert_eq!(vec.get(index2), Some(&(index2, 20)));
+    }
+
+    #[test]
+    fn occupied_bits()
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..200).map(|_| allocator.allocate()).collect();
+
+        let mut vec = ExposedGenVec::new();
+        let mut other = ExposedGenVec::new();
+        for (value, index) in indices.iter().enumerate()
+        {
+            vec.set(*index, value);
+        }
+        for index in &indices[1..199]
+        {
+            vec.remove(*index);
+        }
+        vec.transfer(&mut other, indices[199]).expect("index is valid");
+
+        assert_eq!(vec.occupied_bits().iter().collect::<Vec<usize>>(), vec![0]);
+        assert_eq!(other.occupied_bits().iter().collect::<Vec<usize>>(), vec![199]);
+        assert_eq!(vec.iter_mut().map(|(_, value)| *value).collect::<Vec<usize>>(), vec![0]);
+        assert_eq!(other.into_iter().collect::<Vec<(Index, usize)>>(), vec![(indices[199], 199)]);
+    }
+
+    #[test]
+    fn retain_live()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+
+        let mut vec = ExposedGenVec::new();
+        vec.set(index, 0);
+        vec.set(index1, 1);
+
+        // The recycled generation must not see the old value
+        allocator.deallocate(index);
+        let index2 = allocator.allocate();
+        assert_eq!(vec.get_live(&allocator, index), None);
+        assert_eq!(vec.get_live(&allocator, index2), None);
+
+        assert_eq!(vec.retain_live(&allocator), 1);
+        assert_eq!(vec.retain_live(&allocator), 0);
+        assert_eq!(vec.iter().collect::<Vec<(Index, &i32)>>(), vec![(index1, &1)]);
+        assert_eq!(vec.get_live(&allocator, index1), Some(&1));
+    }
+}
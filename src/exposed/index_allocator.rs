@@ -1,51 +1,303 @@
 use std::
 {
-    vec,
     vec::Vec,
-    collections::VecDeque,
+    collections::{VecDeque, BTreeMap, btree_map},
+    ops::Range,
     iter,
-    slice
+    mem,
+    slice,
+    marker::PhantomData
 };
-use crate::Index;
+#[cfg(feature = "leak-detection")]
+use std::{fmt, panic::Location};
+use crate::{Index, Key, AllocateAtError, CapacityError, OccupancyBits, Ones, ReserveError, Stats};
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
+/// Link marking the end of the free list
+const NIL: u32 = u32::MAX;
+/// `prev` link of an active slot
+const ACTIVE: u32 = u32::MAX - 1;
+/// `prev` link of a freed slot kept out of the free list, either quarantined or retired
+const PARKED: u32 = u32::MAX - 2;
+/// Most slots a non-monotonic `IndexAllocator` tracks, so that every slot can be linked with a `u32`
+const MAX_SLOTS: usize = PARKED as usize;
+
 /// An allocated index of a `IndexAllocator`
+///
+/// While the slot is free, `prev` and `next` link it into the allocator's free list.
+/// Otherwise `prev` marks the slot as active or parked, taking the place of an `is_free` flag
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct AllocatedIndex
 {
-    is_free: bool,
-    generation: usize
+    generation: usize,
+    prev: u32,
+    next: u32,
+    #[cfg(feature = "leak-detection")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    location: Option<&'static Location<'static>>
 }
 
-/// Allocates and deallocates indices for a `ExposedGenVec`
+impl AllocatedIndex
+{
+    #[inline]
+    fn new(state: SlotState) -> AllocatedIndex
+    {
+        let mut allocated_index = AllocatedIndex
+        {
+            generation: 0,
+            prev: PARKED,
+            next: NIL,
+            #[cfg(feature = "leak-detection")]
+            location: None
+        };
+        allocated_index.set_state(state);
+        allocated_index
+    }
+
+    #[inline]
+    fn is_free(&self) -> bool
+    {
+        self.prev != ACTIVE
+    }
+
+    /// Decodes the slot's state from its links
+    #[inline]
+    fn state(&self) -> SlotState
+    {
+        match self.prev
+        {
+            ACTIVE => SlotState::Active,
+            PARKED => SlotState::Parked,
+            prev => SlotState::Free { prev: from_link(prev), next: from_link(self.next) }
+        }
+    }
+
+    #[inline]
+    fn set_state(&mut self, state: SlotState)
+    {
+        let (prev, next) = match state
+        {
+            SlotState::Active => (ACTIVE, NIL),
+            SlotState::Parked => (PARKED, NIL),
+            SlotState::Free { prev, next } => (to_link(prev), to_link(next))
+        };
+        self.prev = prev;
+        self.next = next;
+    }
+}
+
+/// Link to `slot`, which is below `MAX_SLOTS`
+#[inline]
+fn to_link(slot: Option<usize>) -> u32
+{
+    slot.map_or(NIL, |slot| slot as u32)
+}
+
+#[inline]
+fn from_link(link: u32) -> Option<usize>
+{
+    if link == NIL
+    {
+        None
+    }
+    else
+    {
+        Some(link as usize)
+    }
+}
+
+/// Whether a slot is in use and, while it waits to be reused, its neighbours in the free list
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+enum SlotState
+{
+    Active,
+    Free
+    {
+        prev: Option<usize>,
+        next: Option<usize>
+    },
+    /// Freed but kept out of the free list, either quarantined or retired
+    Parked
+}
+
+/// Ends of the free list linked through an `IndexAllocator`'s slots
+///
+/// Slots are reused first in, first out
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct FreeList
+{
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize
+}
+
+/// An index that is still active along with where it was allocated
+#[cfg(feature = "leak-detection")]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Leak<K = Index>
+{
+    /// The index that hasn't been deallocated
+    pub index: K,
+    /// Where `index` was allocated
+    pub location: &'static Location<'static>
+}
+
+#[cfg(feature = "leak-detection")]
+impl<K: Key> fmt::Display for Leak<K>
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "{:?} allocated at {} was never deallocated", self.index, self.location)
+    }
+}
+
+/// Delay before a freed slot can be reused by an `IndexAllocator`
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Quarantine
+{
+    /// Freed slots become reusable once `advance` has been called this many times
+    Ticks(u64),
+    /// Freed slots become reusable once this many other slots have been freed
+    Frees(usize)
+}
+
+/// When a quarantined slot becomes reusable
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Release
+{
+    Tick(u64),
+    Frees(usize)
+}
+
+/// A freed slot waiting out its quarantine
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct QuarantinedSlot
+{
+    slot: usize,
+    release: Release
+}
+
+/// Handle of a consumer of an `IndexAllocator`'s deallocations. See `IndexAllocator::subscribe`
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Subscription(usize);
+
+/// Deallocated indices waiting to be drained by every subscription
 #[derive(Default, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct IndexAllocator
+struct RemovalLog
+{
+    removed: VecDeque<Index>,
+    /// Sequence number of the first entry of `removed`
+    first: u64,
+    /// Sequence number of the next entry each subscription will drain
+    subscriptions: BTreeMap<usize, u64>,
+    next_subscription: usize
+}
+
+impl RemovalLog
+{
+    /// Logs `index` as deallocated, unless nobody is subscribed
+    #[inline]
+    fn record(&mut self, index: Index)
+    {
+        if !self.subscriptions.is_empty()
+        {
+            self.removed.push_back(index);
+        }
+    }
+
+    /// Sequence number of the next entry to be logged
+    fn end(&self) -> u64
+    {
+        self.first + self.removed.len() as u64
+    }
+
+    /// Drops the entries every subscription has drained
+    fn trim(&mut self)
+    {
+        let oldest = self.subscriptions.values().copied().min().unwrap_or_else(| | self.end());
+        while self.first < oldest && self.removed.pop_front().is_some()
+        {
+            self.first += 1;
+        }
+    }
+}
+
+/// Outcome of deallocating an `Index`
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Deallocation
+{
+    /// The index was active and has been freed
+    Freed,
+    /// The index had already been freed
+    AlreadyFree,
+    /// The index doesn't match the current generation of its slot, or its slot was never allocated
+    Stale
+}
+
+/// Allocates and deallocates indices for a `ExposedGenVec`
+///
+/// Hands out keys of type `K`, which is `Index` unless another `Key` is given
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexAllocator<K = Index>
+{
+    free_list: FreeList,
+    active_indices: Vec<AllocatedIndex>,
+    occupied: OccupancyBits,
+    skipped: OccupancyBits,
+    offset: usize,
+    range_end: usize,
+    max_capacity: Option<usize>,
+    strict: bool,
+    quarantine: Option<Quarantine>,
+    quarantined: VecDeque<QuarantinedSlot>,
+    tick: u64,
+    retired_slots: usize,
+    monotonic: bool,
+    live: BTreeMap<usize, AllocatedIndex>,
+    next_slot: usize,
+    freed_generations: BTreeMap<usize, usize>,
+    removals: RemovalLog,
+    total_allocations: usize,
+    total_frees: usize,
+    peak_active: usize,
+    key_type: PhantomData<K>
+}
+
+impl<K: Key> Default for IndexAllocator<K>
 {
-    free_indices: VecDeque<usize>,
-    active_indices: Vec<AllocatedIndex>
+    fn default() -> IndexAllocator<K>
+    {
+        IndexAllocator::with_key()
+    }
 }
 
 impl IndexAllocator
 {
     /// Returns a new empty `IndexAllocator`
     ///
+    /// Free slots are linked with `u32`s, so the allocator tracks at most `u32::MAX - 2` slots
+    /// and `slot_range` ends there. `monotonic` allocators have no such limit
+    ///
     /// # Examples
     ///
     /// ```
     /// use gen_vec::exposed::IndexAllocator;
     /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.slot_range(), 0..u32::MAX as usize - 2);
     /// ```
     pub fn new() -> IndexAllocator
     {
-        IndexAllocator
-        {
-            free_indices: VecDeque::new(),
-            active_indices: Vec::new()
-        }
+        IndexAllocator::with_key()
     }
 
     /// Returns a `IndexAllocator` with initial capacity of `capacity`
@@ -61,228 +313,282 @@ impl IndexAllocator
     /// ```
     pub fn with_capacity(capacity: usize) -> IndexAllocator
     {
-        IndexAllocator
-        {
-            free_indices: VecDeque::with_capacity(capacity),
-            active_indices: Vec::with_capacity(capacity)
-        }
+        IndexAllocator::with_capacity_and_key(capacity)
     }
 
-    /// Allocates and returns a new `Index`
+    /// Returns an empty `IndexAllocator` that will never hold more than `max_capacity` indices at once
     ///
-    /// Activates a freed index if there are any, otherwise creates
-    /// and adds a new index to `active_indices`
+    /// Freed slots keep counting against `max_capacity` until they're reused, so slots waiting out
+    /// a `Quarantine` can make allocation fail while fewer indices are active. See `CapacityError::quarantined`
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::Index;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// let index: Index = allocator.allocate();
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_max_capacity(1);
+    /// assert_eq!(allocator.max_capacity(), Some(1));
+    ///
+    /// assert!(allocator.try_allocate().is_ok());
+    /// assert!(allocator.try_allocate().is_err());
     /// ```
-    pub fn allocate(&mut self) -> Index
+    pub fn with_max_capacity(max_capacity: usize) -> IndexAllocator
     {
-        match self.free_indices.pop_front()
-        {
-            Some(index) =>
-                {
-                    match self.active_indices.get_mut(index)
-                    {
-                        Some(AllocatedIndex{ is_free, generation }) if *is_free =>
-                            {
-                                *is_free = false;
-                                *generation += 1;
-                                Index { index: index, generation: *generation }
-                            },
-                        // Try again if the free index was invalid
-                        _ => self.allocate()
-                    }
-                },
-            _ =>
-                {
-                    self.active_indices.push(AllocatedIndex{ is_free: false, generation: 0 });
-                    Index{ index: self.active_indices.len().saturating_sub(1), generation: 0 }
-                }
-        }
+        IndexAllocator::with_max_capacity_and_key(max_capacity)
     }
 
-    /// Frees `index` if it hasn't been already.
+    /// Returns an empty `IndexAllocator` that only allocates indices within the slots of `range`
     ///
-    /// Afterwards, `index` is added to the pool of free indices
-    /// available for reuse
+    /// Allocators over disjoint ranges never hand out the same `Index`, so their indices can
+    /// be used as keys into the same `ExposedGenVec`
     ///
     /// # Examples
     ///
     /// ```
     /// use gen_vec::Index;
-    /// use gen_vec::exposed::IndexAllocator;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec};
+    ///
+    /// let mut server: IndexAllocator = IndexAllocator::with_range(0..100);
+    /// let mut client: IndexAllocator = IndexAllocator::with_range(100..200);
+    ///
+    /// let server_index: Index = server.allocate();
+    /// let client_index: Index = client.allocate();
+    /// assert_ne!(server_index, client_index);
+    ///
+    /// let mut vec: ExposedGenVec<&str> = ExposedGenVec::new();
+    /// vec.set(server_index, "server");
+    /// vec.set(client_index, "client");
+    /// assert_eq!(vec.get(server_index), Some(&"server"));
+    /// assert_eq!(vec.get(client_index), Some(&"client"));
+    /// ```
+    pub fn with_range(range: Range<usize>) -> IndexAllocator
+    {
+        IndexAllocator::with_range_and_key(range)
+    }
+
+    /// Returns an empty `IndexAllocator` that never reuses a slot once it has been freed
+    ///
+    /// Every allocation gets a fresh slot at generation 0, so an `Index` is never handed out twice.
+    /// Only live indices are tracked, keeping memory proportional to the number of active indices
+    /// rather than the total ever allocated. Store values in a `SparseGenMap` to do the same for them
+    ///
+    /// A bounded slot range (see `split_off`) limits the total number of allocations, not just active ones
+    ///
+    /// `allocate_at` gives up that guarantee for indices of a generation other than 0: their generation is kept
+    /// after they're freed so stale indices can be told apart from double frees, so every such slot ever freed
+    /// takes up memory. Mirroring an allocator that reuses slots costs memory proportional to its total allocations
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let mut map: SparseGenMap<&str> = SparseGenMap::new();
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
     /// let index: Index = allocator.allocate();
+    /// map.set(index, "created");
+    ///
     /// allocator.deallocate(index);
+    /// map.remove(index);
+    ///
+    /// let index1: Index = allocator.allocate();
+    /// assert_ne!(index, index1);
+    /// assert_eq!(allocator.num_free(), 0);
     /// ```
-    pub fn deallocate(&mut self, index: Index)
+    pub fn monotonic() -> IndexAllocator
     {
-        if self.is_active(index)
-        {
-            self.active_indices[index.index].is_free = true;
-            self.free_indices.push_back(index.index);
-        }
+        IndexAllocator::monotonic_with_key()
     }
+}
 
-    /// Frees all active indices and adds them to the pool of free indices
+impl<K: Key> IndexAllocator<K>
+{
+    /// Returns a new empty `IndexAllocator` that hands out keys of type `K`
     ///
     /// # Examples
     ///
     /// ```
+    /// use gen_vec::new_key_type;
     /// use gen_vec::exposed::IndexAllocator;
-    /// use gen_vec::Index;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// for _ in 0..10
-    /// {
-    ///     allocator.allocate();
-    /// }
-    /// assert_eq!(allocator.num_active(), 10);
-    /// assert_eq!(allocator.num_free(), 0);
+    /// new_key_type! { struct EntityId; }
     ///
-    /// allocator.deallocate_all();
-    /// assert_eq!(allocator.num_active(), 0);
-    /// assert_eq!(allocator.num_free(), 10);
+    /// let mut allocator: IndexAllocator<EntityId> = IndexAllocator::with_key();
+    /// let id: EntityId = allocator.allocate();
     /// ```
-    pub fn deallocate_all(&mut self)
+    pub fn with_key() -> IndexAllocator<K>
     {
-        for (index, alloc_index) in self.active_indices.iter_mut().enumerate()
+        IndexAllocator
         {
-            alloc_index.is_free = true;
-            self.free_indices.push_back(index);
+            free_list: FreeList::default(),
+            active_indices: Vec::new(),
+            occupied: OccupancyBits::new(),
+            skipped: OccupancyBits::new(),
+            offset: 0,
+            range_end: usize::MAX,
+            max_capacity: None,
+            strict: false,
+            quarantine: None,
+            quarantined: VecDeque::new(),
+            tick: 0,
+            retired_slots: 0,
+            monotonic: false,
+            live: BTreeMap::new(),
+            next_slot: 0,
+            freed_generations: BTreeMap::new(),
+            removals: RemovalLog::default(),
+            total_allocations: 0,
+            total_frees: 0,
+            peak_active: 0,
+            key_type: PhantomData
         }
     }
 
-    /// Reserved capacity within the `IndexAllocator`
+    /// Returns a `IndexAllocator` with initial capacity of `capacity` that hands out keys of type `K`
     ///
     /// # Examples
     ///
     /// ```
+    /// use gen_vec::new_key_type;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::with_capacity(5);
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let allocator: IndexAllocator<EntityId> = IndexAllocator::with_capacity_and_key(5);
     /// assert_eq!(allocator.capacity(), 5);
     /// ```
-    pub fn capacity(&self) -> usize
+    pub fn with_capacity_and_key(capacity: usize) -> IndexAllocator<K>
     {
-        self.active_indices.capacity()
+        IndexAllocator
+        {
+            active_indices: Vec::with_capacity(capacity),
+            ..IndexAllocator::with_key()
+        }
     }
 
-    /// Reserves extra space for *at least* `additional` more elements
+    /// Returns an empty `IndexAllocator` that will never hold more than `max_capacity` keys of type `K` at once
     ///
-    /// More space may be allocated to avoid frequent re-allocations
-    /// (as per the specifications of std::vec::Vec)
+    /// Quarantined slots count against `max_capacity`. See `with_max_capacity`
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::Index;
+    /// use gen_vec::new_key_type;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// let index: Index = allocator.allocate();
-    /// allocator.reserve(4);
-    /// assert!(allocator.capacity() >= 4);
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let allocator: IndexAllocator<EntityId> = IndexAllocator::with_max_capacity_and_key(1);
+    /// assert_eq!(allocator.max_capacity(), Some(1));
     /// ```
-    pub fn reserve(&mut self, additional: usize)
+    pub fn with_max_capacity_and_key(max_capacity: usize) -> IndexAllocator<K>
     {
-        if additional > 0
+        IndexAllocator
         {
-            self.active_indices.reserve(additional);
-            self.free_indices.reserve(additional);
-
-            if self.active_indices.len() > 0
-            {
-                let last_index = self.active_indices.len().saturating_sub(1);
-                // Add all new reserved
-                for i in last_index..(last_index+additional)
-                {
-                    self.free_indices.push_back(i);
-                    self.active_indices.push(AllocatedIndex{ is_free: true, generation: 0 });
-                }
-            }
+            max_capacity: Some(max_capacity),
+            ..IndexAllocator::with_key()
         }
     }
 
-    /// Returns if `index` is still active and hasn't been deallocated
+    /// Returns an empty `IndexAllocator` that only allocates keys of type `K` within the slots of `range`
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::Index;
+    /// use gen_vec::new_key_type;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// let index: Index = allocator.allocate();
-    /// assert!(allocator.is_active(index));
-    /// allocator.deallocate(index);
-    /// assert!(!allocator.is_active(index));
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let allocator: IndexAllocator<EntityId> = IndexAllocator::with_range_and_key(10..20);
+    /// assert_eq!(allocator.slot_range(), 10..20);
     /// ```
-    pub fn is_active(&self, index: Index) -> bool
+    pub fn with_range_and_key(range: Range<usize>) -> IndexAllocator<K>
     {
-        match self.active_indices.get(index.index)
+        IndexAllocator
         {
-            Some(AllocatedIndex{ is_free, generation }) => *generation == index.generation && !*is_free,
-            _ => false
+            occupied: OccupancyBits::with_offset(range.start),
+            skipped: OccupancyBits::with_offset(range.start),
+            offset: range.start,
+            range_end: range.end.max(range.start),
+            ..IndexAllocator::with_key()
         }
     }
 
-    /// Returns the number of free indices waiting to be allocated and reused
+    /// Returns an empty `IndexAllocator` that never reuses a slot and hands out keys of type `K`. See `monotonic`
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::Index;
+    /// use gen_vec::new_key_type;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// assert_eq!(allocator.num_free(), 0);
+    /// new_key_type! { struct EntityId; }
     ///
-    /// let index: Index = allocator.allocate();
+    /// let allocator: IndexAllocator<EntityId> = IndexAllocator::monotonic_with_key();
+    /// assert!(allocator.is_monotonic());
+    /// ```
+    pub fn monotonic_with_key() -> IndexAllocator<K>
+    {
+        IndexAllocator
+        {
+            monotonic: true,
+            ..IndexAllocator::with_key()
+        }
+    }
+
+    /// Returns `true` if freed slots are never reused. See `monotonic`
     ///
-    /// allocator.deallocate(index);
-    /// assert_eq!(allocator.num_free(), 1);
+    /// # Examples
     ///
-    /// let index: Index = allocator.allocate();
-    /// assert_eq!(allocator.num_free(), 0);
     /// ```
-    pub fn num_free(&self) -> usize
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// assert!(IndexAllocator::monotonic().is_monotonic());
+    /// assert!(!IndexAllocator::new().is_monotonic());
+    /// ```
+    pub fn is_monotonic(&self) -> bool
     {
-        self.free_indices.len()
+        self.monotonic
     }
 
-    /// Returns the number of active indices
+    /// Range of slots this allocator allocates indices within
+    ///
+    /// Unless the allocator is `monotonic`, the range holds at most `u32::MAX - 2` slots
     ///
     /// # Examples
     ///
     /// ```
-    /// use gen_vec::Index;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// assert_eq!(allocator.num_active(), 0);
+    /// let allocator: IndexAllocator = IndexAllocator::with_range(10..20);
+    /// assert_eq!(allocator.slot_range(), 10..20);
     ///
-    /// let index: Index = allocator.allocate();
-    /// assert_eq!(allocator.num_active(), 1);
+    /// let allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.slot_range(), 0..u32::MAX as usize - 2);
     ///
-    /// allocator.deallocate(index);
-    /// assert_eq!(allocator.num_active(), 0);
+    /// let allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// assert_eq!(allocator.slot_range(), 0..usize::MAX);
     /// ```
-    pub fn num_active(&self) -> usize
+    pub fn slot_range(&self) -> Range<usize>
     {
-        self.active_indices.len().saturating_sub(self.free_indices.len())
+        if self.monotonic
+        {
+            self.offset..self.range_end
+        }
+        else
+        {
+            self.offset..self.range_end.min(self.offset.saturating_add(MAX_SLOTS))
+        }
     }
 
-    /// Returns an iterator over an immutable `IndexAllocator`
-    /// Each step returns an `Index`
+    /// Splits the allocator in two at slot `at`
+    ///
+    /// Afterwards, `self` only allocates slots before `at` and the returned allocator only allocates
+    /// slots from `at` onwards. Indices already allocated from slots past `at` move to the returned allocator.
+    /// Allocation totals stay with `self`
     ///
     /// # Examples
     ///
@@ -290,95 +596,1415 @@ impl IndexAllocator
     /// use gen_vec::Index;
     /// use gen_vec::exposed::IndexAllocator;
     ///
-    /// let mut allocator: IndexAllocator = IndexAllocator::new();
-    /// allocator.allocate();
-    /// allocator.allocate();
+    /// let mut server: IndexAllocator = IndexAllocator::new();
+    /// let mut client: IndexAllocator = server.split_off(1000);
+    /// assert_eq!(server.slot_range(), 0..1000);
+    /// assert_eq!(client.slot_range(), 1000..1000 + u32::MAX as usize - 2);
     ///
-    /// for index in allocator
-    /// {
-    ///     println!("{:?}", index);
-    /// }
+    /// let index: Index = client.allocate();
+    /// assert!(client.is_active(index));
+    /// assert!(!server.is_active(index));
     /// ```
-    pub fn iter(&self) -> Iter
+    ///
+    /// # Panics
+    ///
+    /// If `at` is outside of the allocator's range. `at` may lie past the end of `slot_range`
+    /// when the slot limit of a non-`monotonic` allocator ends the range early
+    pub fn split_off(&mut self, at: usize) -> IndexAllocator<K>
     {
-        Iter
+        let range = self.offset..self.range_end;
+        assert!(range.start <= at && at <= range.end, "Split point {} is outside of slot range {:?}", at, range);
+
+        let local_at = at - self.offset;
+        // Relink both free lists once the slots have been divided, keeping their order
+        let (free_indices, kept): (Vec<usize>, Vec<usize>) = self.free_slots().partition(|slot| *slot >= local_at);
+        self.free_list = FreeList::default();
+
+        let active_indices = if local_at < self.active_indices.len()
         {
-            internal: self.active_indices.iter().enumerate()
+            self.active_indices.split_off(local_at)
         }
-    }
-}
+        else
+        {
+            Vec::new()
+        };
 
-/// Struct for consuming a `IndexAllocator` into an iterator
-#[derive(Debug)]
-pub struct IntoIter
-{
-    internal: iter::Enumerate<vec::IntoIter<AllocatedIndex>>
-}
+        for slot in kept
+        {
+            self.push_free(slot);
+        }
 
-impl Iterator for IntoIter
-{
-    type Item = Index;
+        let (quarantined, kept): (VecDeque<QuarantinedSlot>, VecDeque<QuarantinedSlot>) = self.quarantined.drain(..).partition(|quarantined| quarantined.slot >= local_at);
+        self.quarantined = kept;
+        let total_frees = self.total_frees;
+        let quarantined = quarantined.into_iter()
+                                     .map(|QuarantinedSlot { slot, release }| QuarantinedSlot
+                                     {
+                                         slot: slot - local_at,
+                                         // Frees are counted from zero in the new allocator
+                                         release: match release
+                                         {
+                                             Release::Frees(frees) => Release::Frees(frees.saturating_sub(total_frees)),
+                                             release => release
+                                         }
+                                     })
+                                     .collect();
 
-    fn next(&mut self) -> Option<Self::Item>
-    {
-        loop
-        {
-            match self.internal.next()
+        let live: BTreeMap<usize, AllocatedIndex> = self.live.split_off(&local_at)
+                                                        .into_iter()
+                                                        .map(|(slot, allocated_index)| (slot - local_at, allocated_index))
+                                                        .collect();
+        let next_slot = self.next_slot.saturating_sub(local_at);
+        self.next_slot = self.next_slot.min(local_at);
+        let freed_generations: BTreeMap<usize, usize> = self.freed_generations.split_off(&local_at)
+                                                                  .into_iter()
+                                                                  .map(|(slot, generation)| (slot - local_at, generation))
+                                                                  .collect();
+
+        let retired_slots = active_indices.iter()
+                                          .filter(|allocated_index| allocated_index.is_free() && allocated_index.generation == usize::MAX)
+                                          .count();
+        self.retired_slots -= retired_slots;
+
+        let mut other = IndexAllocator
+        {
+            active_indices,
+            occupied: self.occupied.split_off(at),
+            skipped: self.skipped.split_off(at),
+            offset: at,
+            range_end: self.range_end,
+            max_capacity: self.max_capacity,
+            strict: self.strict,
+            quarantine: self.quarantine,
+            quarantined,
+            tick: self.tick,
+            retired_slots,
+            monotonic: self.monotonic,
+            live,
+            next_slot,
+            freed_generations,
+            ..IndexAllocator::with_key()
+        };
+        for slot in free_indices
+        {
+            other.push_free(slot - local_at);
+        }
+        other.peak_active = other.num_active();
+        self.range_end = at;
+        other
+    }
+
+    /// The maximum number of indices that can be allocated at once, if bounded
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.max_capacity(), None);
+    ///
+    /// let allocator: IndexAllocator = IndexAllocator::with_max_capacity(10);
+    /// assert_eq!(allocator.max_capacity(), Some(10));
+    /// ```
+    pub fn max_capacity(&self) -> Option<usize>
+    {
+        self.max_capacity
+    }
+
+    /// Sets the maximum number of indices that can be allocated at once
+    ///
+    /// The maximum capacity is separate from the slot range: an allocator with a range
+    /// can be capped below the length of its range. Indices already allocated are kept
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_range(10..20);
+    /// allocator.set_max_capacity(Some(1));
+    /// assert_eq!(allocator.slot_range(), 10..20);
+    ///
+    /// allocator.allocate();
+    /// assert!(allocator.try_allocate().is_err());
+    /// ```
+    pub fn set_max_capacity(&mut self, max_capacity: Option<usize>)
+    {
+        self.max_capacity = max_capacity;
+    }
+
+    /// Allocates and returns a new `Index`
+    ///
+    /// Activates a freed index if there are any, otherwise creates
+    /// and adds a new index to `active_indices`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the maximum capacity has been reached. See `try_allocate`
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn allocate(&mut self) -> K
+    {
+        self.try_allocate().unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    /// Allocates and returns a new `Index`, or an error if the maximum capacity has been reached
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_max_capacity(1);
+    /// let index: Index = allocator.try_allocate().expect("Should have room for one index");
+    /// assert!(allocator.try_allocate().is_err());
+    ///
+    /// // Freed indices can still be reused
+    /// allocator.deallocate(index);
+    /// assert!(allocator.try_allocate().is_ok());
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn try_allocate(&mut self) -> Result<K, CapacityError>
+    {
+        let index = self.next_index()?;
+        #[cfg(feature = "leak-detection")]
+        {
+            if let Some(allocated_index) = self.allocated_mut(index.index - self.offset)
+            {
+                allocated_index.location = Some(Location::caller());
+            }
+        }
+        self.total_allocations += 1;
+        self.peak_active = self.peak_active.max(self.num_active());
+        Ok(index.into_key())
+    }
+
+    /// Activates and returns the next available index
+    fn next_index(&mut self) -> Result<Index, CapacityError>
+    {
+        if self.monotonic
+        {
+            return self.next_monotonic_index();
+        }
+
+        match self.pop_free()
+        {
+            Some(index) =>
+                {
+                    let allocated_index = &mut self.active_indices[index];
+                    allocated_index.set_state(SlotState::Active);
+                    allocated_index.generation += 1;
+                    self.occupied.insert(self.offset + index);
+                    self.skipped.remove(self.offset + index);
+                    Ok(Index { index: self.offset + index, generation: allocated_index.generation })
+                },
+            _ =>
+                {
+                    match self.slot_limit()
+                    {
+                        Some(limit) if self.used_slots() >= limit =>
+                            Err(CapacityError::new((), limit).with_quarantined(self.quarantined.len())),
+                        _ if self.active_indices.len() >= MAX_SLOTS => Err(CapacityError::new((), MAX_SLOTS)),
+                        _ =>
+                            {
+                                self.active_indices.push(AllocatedIndex::new(SlotState::Active));
+                                let index = self.offset + self.active_indices.len().saturating_sub(1);
+                                self.occupied.insert(index);
+                                Ok(Index{ index, generation: 0 })
+                            }
+                    }
+                }
+        }
+    }
+
+    /// Activates and returns a never before used slot
+    fn next_monotonic_index(&mut self) -> Result<Index, CapacityError>
+    {
+        match self.slot_limit()
+        {
+            Some(limit) if self.used_slots() >= limit => Err(CapacityError::new((), limit)),
+            _ =>
+                {
+                    let slot = self.next_slot;
+                    self.next_slot += 1;
+                    self.live.insert(slot, AllocatedIndex::new(SlotState::Active));
+                    Ok(Index { index: self.offset + slot, generation: 0 })
+                }
+        }
+    }
+
+    /// Allocates exactly `index`, growing the allocator if its slot hasn't been reached yet
+    ///
+    /// Useful for mirroring indices chosen by another allocator. Any slots skipped over
+    /// while growing are added to the pool of free indices. An active index of an older
+    /// generation in the same slot is replaced. A freed slot only takes a generation newer
+    /// than the one it was freed at, so freed indices stay freed
+    ///
+    /// A monotonic allocator skips past the slots in between instead, and refuses
+    /// slots that have already been freed. It remembers the generation of freed slots
+    /// allocated this way at a generation other than 0, see `monotonic`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, AllocateAtError};
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut server: IndexAllocator = IndexAllocator::new();
+    /// server.allocate();
+    /// let index: Index = server.allocate();
+    ///
+    /// let mut client: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(client.allocate_at(index), Ok(()));
+    /// assert!(client.is_active(index));
+    /// assert_eq!(client.num_active(), 1);
+    /// assert_eq!(client.num_free(), 1);
+    ///
+    /// assert_eq!(client.allocate_at(index), Err(AllocateAtError::Occupied));
+    /// ```
+    #[cfg_attr(feature = "leak-detection", track_caller)]
+    pub fn allocate_at(&mut self, index: K) -> Result<(), AllocateAtError>
+    {
+        let index = Index::from_key(index);
+        if !self.slot_range().contains(&index.index)
+        {
+            return Err(AllocateAtError::OutOfRange);
+        }
+
+        let slot = index.index - self.offset;
+        if let Some(max_capacity) = self.max_capacity
+        {
+            if slot >= max_capacity
+            {
+                return Err(AllocateAtError::MaxCapacityExceeded { max_capacity });
+            }
+        }
+
+        if self.monotonic
+        {
+            self.allocate_monotonic_at(slot, index.generation)?;
+        }
+        else
+        {
+            self.allocate_dense_at(slot, index.generation)?;
+        }
+
+        #[cfg(feature = "leak-detection")]
+        {
+            if let Some(allocated_index) = self.allocated_mut(slot)
+            {
+                allocated_index.location = Some(Location::caller());
+            }
+        }
+        self.total_allocations += 1;
+        self.peak_active = self.peak_active.max(self.num_active());
+        Ok(())
+    }
+
+    /// Activates `slot` at `generation`, growing `active_indices` to reach it
+    fn allocate_dense_at(&mut self, slot: usize, requested: usize) -> Result<(), AllocateAtError>
+    {
+        if slot >= MAX_SLOTS
+        {
+            return Err(AllocateAtError::OutOfRange);
+        }
+
+        while self.active_indices.len() <= slot
+        {
+            self.active_indices.push(AllocatedIndex::new(SlotState::Parked));
+            self.push_free(self.active_indices.len() - 1);
+            self.skipped.insert(self.offset + self.active_indices.len() - 1);
+        }
+
+        let (state, generation) = (self.active_indices[slot].state(), self.active_indices[slot].generation);
+        // A freed slot's generation has been handed out before, so only newer generations may take it
+        let stale = match state
+        {
+            SlotState::Active => generation > requested,
+            _ => generation >= requested && !self.skipped.contains(self.offset + slot)
+        };
+        if stale
+        {
+            return Err(AllocateAtError::Stale { current_generation: generation });
+        }
+
+        match state
+        {
+            SlotState::Active if generation == requested => return Err(AllocateAtError::Occupied),
+            SlotState::Active =>
+                {
+                    // Older generation is implicitly freed
+                    self.total_frees += 1;
+                    self.removals.record(Index { index: self.offset + slot, generation });
+                },
+            SlotState::Free { .. } => self.unlink_free(slot),
+            SlotState::Parked => match self.quarantined.iter().position(|quarantined| quarantined.slot == slot)
+            {
+                Some(position) => { self.quarantined.remove(position); },
+                // Parked slots missing from the quarantine have been retired
+                _ => self.retired_slots -= 1
+            }
+        }
+
+        let allocated_index = &mut self.active_indices[slot];
+        allocated_index.set_state(SlotState::Active);
+        allocated_index.generation = requested;
+        self.occupied.insert(self.offset + slot);
+        self.skipped.remove(self.offset + slot);
+        Ok(())
+    }
+
+    /// Activates `slot` at `generation`, moving the next allocated slot past it
+    fn allocate_monotonic_at(&mut self, slot: usize, requested: usize) -> Result<(), AllocateAtError>
+    {
+        match self.live.get(&slot)
+        {
+            Some(AllocatedIndex { generation, .. }) if *generation > requested =>
+                return Err(AllocateAtError::Stale { current_generation: *generation }),
+            Some(AllocatedIndex { generation, .. }) if *generation == requested => return Err(AllocateAtError::Occupied),
+            // Older generation is implicitly freed
+            Some(AllocatedIndex { generation, .. }) =>
+                {
+                    self.total_frees += 1;
+                    self.removals.record(Index { index: self.offset + slot, generation: *generation });
+                },
+            None if slot < self.next_slot => return Err(AllocateAtError::Retired),
+            None => self.next_slot = slot + 1
+        }
+
+        self.live.insert(slot, AllocatedIndex { generation: requested, ..AllocatedIndex::new(SlotState::Active) });
+        Ok(())
+    }
+
+    /// Frees `index` if it hasn't been already.
+    ///
+    /// Afterwards, `index` is added to the pool of free indices
+    /// available for reuse
+    ///
+    /// Returns whether `index` was freed, had already been freed, or is stale
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, Deallocation};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// assert_eq!(allocator.deallocate(index), Deallocation::Freed);
+    /// assert_eq!(allocator.deallocate(index), Deallocation::AlreadyFree);
+    ///
+    /// allocator.allocate();
+    /// assert_eq!(allocator.deallocate(index), Deallocation::Stale);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// In strict mode, if `index` had already been freed or is stale. See `set_strict`
+    pub fn deallocate(&mut self, index: K) -> Deallocation
+    {
+        let index = Index::from_key(index);
+        let slot = self.slot(index);
+        let deallocation = match slot.and_then(|slot| self.allocated(slot))
+        {
+            Some(AllocatedIndex{ generation, .. }) if *generation != index.generation => Deallocation::Stale,
+            Some(allocated_index) if allocated_index.is_free() => Deallocation::AlreadyFree,
+            Some(_) =>
+                {
+                    self.free_slot(index.index - self.offset);
+                    Deallocation::Freed
+                },
+            // Monotonic allocators forget slots once they're freed, except for generations set by `allocate_at`
+            None if self.monotonic && matches!(slot, Some(slot) if slot < self.next_slot) =>
+                {
+                    let freed_generation = slot.and_then(|slot| self.freed_generations.get(&slot)).copied().unwrap_or(0);
+                    if index.generation == freed_generation
+                    {
+                        Deallocation::AlreadyFree
+                    }
+                    else
+                    {
+                        Deallocation::Stale
+                    }
+                },
+            None => Deallocation::Stale
+        };
+
+        if self.strict
+        {
+            match deallocation
+            {
+                Deallocation::AlreadyFree => panic!("Double free of {:?}", index),
+                Deallocation::Stale => panic!("Deallocating stale {:?}", index),
+                Deallocation::Freed => ()
+            }
+        }
+
+        deallocation
+    }
+
+    /// Starts logging deallocations for a new subscription and returns it
+    ///
+    /// Every index freed from now on, whether by `deallocate`, `deallocate_all` or implicitly by `allocate_at`,
+    /// is kept until each subscription has drained it. Use this to purge values, caches or spatial indexes
+    /// keyed by freed indices without polling
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, ExposedGenVec, Subscription};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let subscription: Subscription = allocator.subscribe();
+    ///
+    /// let mut vec: ExposedGenVec<i32> = ExposedGenVec::new();
+    /// let index: Index = allocator.allocate();
+    /// vec.set(index, 0);
+    ///
+    /// allocator.deallocate(index);
+    /// for removed in allocator.drain_removed(subscription)
+    /// {
+    ///     vec.remove(removed);
+    /// }
+    /// assert!(!vec.contains(index));
+    /// ```
+    pub fn subscribe(&mut self) -> Subscription
+    {
+        let subscription = self.removals.next_subscription;
+        self.removals.next_subscription += 1;
+        let end = self.removals.end();
+        self.removals.subscriptions.insert(subscription, end);
+        Subscription(subscription)
+    }
+
+    /// Stops logging deallocations for `subscription`, returning `false` if it wasn't subscribed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let subscription = allocator.subscribe();
+    /// assert!(allocator.unsubscribe(subscription));
+    /// assert!(!allocator.unsubscribe(subscription));
+    /// ```
+    pub fn unsubscribe(&mut self, subscription: Subscription) -> bool
+    {
+        let unsubscribed = self.removals.subscriptions.remove(&subscription.0).is_some();
+        self.removals.trim();
+        unsubscribed
+    }
+
+    /// Returns the indices freed since `subscription` was last drained, in the order they were freed
+    ///
+    /// Returns nothing if `subscription` isn't subscribed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// let subscription = allocator.subscribe();
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.drain_removed(subscription), vec![index]);
+    /// assert!(allocator.drain_removed(subscription).is_empty());
+    /// ```
+    pub fn drain_removed(&mut self, subscription: Subscription) -> Vec<K>
+    {
+        let end = self.removals.end();
+        let next = match self.removals.subscriptions.get_mut(&subscription.0)
+        {
+            Some(next) => mem::replace(next, end),
+            _ => return Vec::new()
+        };
+
+        let skip = (next - self.removals.first) as usize;
+        let removed = self.removals.removed
+                                   .iter()
+                                   .skip(skip)
+                                   .map(|index| index.into_key())
+                                   .collect();
+        self.removals.trim();
+        removed
+    }
+
+    /// Enables or disables strict mode
+    ///
+    /// In strict mode, `deallocate` panics when given an index that
+    /// has already been freed or is stale instead of ignoring it
+    ///
+    /// # Examples
+    ///
+    /// ```should_panic
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// allocator.set_strict(true);
+    ///
+    /// let index: Index = allocator.allocate();
+    /// allocator.deallocate(index);
+    /// allocator.deallocate(index); // Panics
+    /// ```
+    pub fn set_strict(&mut self, strict: bool)
+    {
+        self.strict = strict;
+    }
+
+    /// Returns `true` if strict mode is enabled. See `set_strict`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// assert!(!allocator.is_strict());
+    ///
+    /// allocator.set_strict(true);
+    /// assert!(allocator.is_strict());
+    /// ```
+    pub fn is_strict(&self) -> bool
+    {
+        self.strict
+    }
+
+    /// Position of `index` within `active_indices`, if it's within this allocator's range
+    fn slot(&self, index: Index) -> Option<usize>
+    {
+        index.index.checked_sub(self.offset)
+    }
+
+    /// Allocation state of `slot`, if it's being tracked
+    fn allocated(&self, slot: usize) -> Option<&AllocatedIndex>
+    {
+        if self.monotonic
+        {
+            self.live.get(&slot)
+        }
+        else
+        {
+            self.active_indices.get(slot)
+        }
+    }
+
+    /// Mutable allocation state of `slot`, if it's being tracked
+    #[cfg(feature = "leak-detection")]
+    fn allocated_mut(&mut self, slot: usize) -> Option<&mut AllocatedIndex>
+    {
+        if self.monotonic
+        {
+            self.live.get_mut(&slot)
+        }
+        else
+        {
+            self.active_indices.get_mut(slot)
+        }
+    }
+
+    /// Every tracked slot, free or not, in ascending order
+    fn slots(&self) -> Slots<'_>
+    {
+        if self.monotonic
+        {
+            Slots::Sparse(self.live.iter())
+        }
+        else
+        {
+            Slots::Dense(self.active_indices.iter().enumerate())
+        }
+    }
+
+    /// Marks `slot` as free and queues it for reuse
+    ///
+    /// Slots that have exhausted their generations are retired instead of reused.
+    /// Monotonic allocators stop tracking the slot altogether
+    fn free_slot(&mut self, slot: usize)
+    {
+        if self.monotonic
+        {
+            if let Some(AllocatedIndex { generation, .. }) = self.live.remove(&slot)
+            {
+                self.forget_live(slot, generation);
+            }
+            self.total_frees += 1;
+            return;
+        }
+
+        self.occupied.remove(self.offset + slot);
+        let allocated_index = &mut self.active_indices[slot];
+        allocated_index.set_state(SlotState::Parked);
+        self.total_frees += 1;
+        self.removals.record(Index { index: self.offset + slot, generation: allocated_index.generation });
+
+        if allocated_index.generation == usize::MAX
+        {
+            self.retired_slots += 1;
+        }
+        else
+        {
+            match self.quarantine
+            {
+                Some(Quarantine::Ticks(ticks)) =>
+                    self.quarantined.push_back(QuarantinedSlot { slot, release: Release::Tick(self.tick.saturating_add(ticks)) }),
+                Some(Quarantine::Frees(frees)) =>
+                    self.quarantined.push_back(QuarantinedSlot { slot, release: Release::Frees(self.total_frees.saturating_add(frees)) }),
+                None => self.push_free(slot)
+            }
+        }
+        self.release_quarantined();
+    }
+
+    /// Stops tracking a freed slot of a monotonic allocator
+    ///
+    /// Slots are only ever allocated at generation 0 unless `allocate_at` says otherwise,
+    /// so only other generations are kept to tell double frees apart from stale indices
+    fn forget_live(&mut self, slot: usize, generation: usize)
+    {
+        if generation != 0
+        {
+            self.freed_generations.insert(slot, generation);
+        }
+        self.removals.record(Index { index: self.offset + slot, generation });
+    }
+
+    /// Links the parked `slot` onto the back of the free list
+    fn push_free(&mut self, slot: usize)
+    {
+        let tail = self.free_list.tail;
+        self.active_indices[slot].set_state(SlotState::Free { prev: tail, next: None });
+        match tail
+        {
+            Some(tail) => self.active_indices[tail].next = to_link(Some(slot)),
+            _ => self.free_list.head = Some(slot)
+        }
+        self.free_list.tail = Some(slot);
+        self.free_list.len += 1;
+    }
+
+    /// Unlinks and returns the slot at the front of the free list, leaving it parked
+    fn pop_free(&mut self) -> Option<usize>
+    {
+        let slot = self.free_list.head?;
+        self.unlink_free(slot);
+        Some(slot)
+    }
+
+    /// Unlinks `slot` from anywhere in the free list, leaving it parked
+    fn unlink_free(&mut self, slot: usize)
+    {
+        let (prev, next) = match self.active_indices[slot].state()
+        {
+            SlotState::Free { prev, next } => (prev, next),
+            state => panic!("Slot {} in the free list is {:?}", slot, state)
+        };
+        match prev
+        {
+            Some(prev) => self.active_indices[prev].next = to_link(next),
+            _ => self.free_list.head = next
+        }
+        match next
+        {
+            Some(next) => self.active_indices[next].prev = to_link(prev),
+            _ => self.free_list.tail = prev
+        }
+        self.free_list.len -= 1;
+        self.active_indices[slot].set_state(SlotState::Parked);
+    }
+
+    /// Every slot in the free list, front to back
+    fn free_slots(&self) -> impl Iterator<Item = usize> + '_
+    {
+        iter::successors(self.free_list.head, move |slot| match self.active_indices[*slot].state()
+        {
+            SlotState::Free { next, .. } => next,
+            state => panic!("Slot {} in the free list is {:?}", slot, state)
+        })
+    }
+
+    /// Moves quarantined slots that have waited long enough to the free list
+    ///
+    /// Slots are released in the order they were freed
+    fn release_quarantined(&mut self)
+    {
+        while let Some(quarantined) = self.quarantined.front()
+        {
+            let released = match quarantined.release
+            {
+                Release::Tick(tick) => self.tick >= tick,
+                Release::Frees(frees) => self.total_frees >= frees
+            };
+            if !released
+            {
+                break;
+            }
+
+            if let Some(quarantined) = self.quarantined.pop_front()
+            {
+                self.push_free(quarantined.slot);
+            }
+        }
+    }
+
+    /// Sets how long freed slots must wait before they can be reused
+    ///
+    /// Freed slots are otherwise reused as soon as every slot freed before them has been reused.
+    /// Slots already in quarantine keep the delay they were freed with.
+    /// Removing the quarantine makes every quarantined slot reusable immediately.
+    /// Quarantined slots still count against the maximum capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, Quarantine};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// allocator.set_quarantine(Some(Quarantine::Ticks(2)));
+    ///
+    /// let index: Index = allocator.allocate();
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.num_quarantined(), 1);
+    /// assert_eq!(allocator.num_free(), 0);
+    ///
+    /// allocator.advance();
+    /// allocator.advance();
+    /// assert_eq!(allocator.num_quarantined(), 0);
+    /// assert_eq!(allocator.num_free(), 1);
+    /// ```
+    pub fn set_quarantine(&mut self, quarantine: Option<Quarantine>)
+    {
+        self.quarantine = quarantine;
+        if quarantine.is_none()
+        {
+            for quarantined in mem::take(&mut self.quarantined)
+            {
+                self.push_free(quarantined.slot);
+            }
+        }
+    }
+
+    /// How long freed slots must wait before they can be reused, if at all. See `set_quarantine`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::{IndexAllocator, Quarantine};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.quarantine(), None);
+    ///
+    /// allocator.set_quarantine(Some(Quarantine::Frees(10)));
+    /// assert_eq!(allocator.quarantine(), Some(Quarantine::Frees(10)));
+    /// ```
+    pub fn quarantine(&self) -> Option<Quarantine>
+    {
+        self.quarantine
+    }
+
+    /// Advances the allocator by one tick, releasing slots whose `Quarantine::Ticks` delay has passed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, Quarantine};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// allocator.set_quarantine(Some(Quarantine::Ticks(1)));
+    ///
+    /// let index: Index = allocator.allocate();
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.num_quarantined(), 1);
+    ///
+    /// allocator.advance();
+    /// assert_eq!(allocator.num_quarantined(), 0);
+    /// assert_eq!(allocator.num_free(), 1);
+    /// ```
+    pub fn advance(&mut self)
+    {
+        self.tick += 1;
+        self.release_quarantined();
+    }
+
+    /// Returns the number of freed indices waiting out their quarantine
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, Quarantine};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// allocator.set_quarantine(Some(Quarantine::Frees(1)));
+    ///
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.num_quarantined(), 1);
+    ///
+    /// allocator.deallocate(index1);
+    /// assert_eq!(allocator.num_quarantined(), 1);
+    /// assert_eq!(allocator.num_free(), 1);
+    /// ```
+    pub fn num_quarantined(&self) -> usize
+    {
+        self.quarantined.len()
+    }
+
+    /// Frees all active indices and adds them to the pool of free indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    /// use gen_vec::Index;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// for _ in 0..10
+    /// {
+    ///     allocator.allocate();
+    /// }
+    /// assert_eq!(allocator.num_active(), 10);
+    /// assert_eq!(allocator.num_free(), 0);
+    ///
+    /// allocator.deallocate_all();
+    /// assert_eq!(allocator.num_active(), 0);
+    /// assert_eq!(allocator.num_free(), 10);
+    /// ```
+    pub fn deallocate_all(&mut self)
+    {
+        if self.monotonic
+        {
+            self.total_frees += self.live.len();
+            for (slot, allocated_index) in mem::take(&mut self.live)
+            {
+                self.forget_live(slot, allocated_index.generation);
+            }
+            return;
+        }
+
+        for slot in 0..self.active_indices.len()
+        {
+            if !self.active_indices[slot].is_free()
+            {
+                self.free_slot(slot);
+            }
+        }
+    }
+
+    /// Reserved capacity within the `IndexAllocator`
+    ///
+    /// Monotonic allocators only track live indices and never reserve capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_capacity(5);
+    /// assert_eq!(allocator.capacity(), 5);
+    /// ```
+    pub fn capacity(&self) -> usize
+    {
+        self.active_indices.capacity()
+    }
+
+    /// Reserves extra space for *at least* `additional` more elements
+    ///
+    /// More space may be allocated to avoid frequent re-allocations
+    /// (as per the specifications of std::vec::Vec)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// allocator.reserve(4);
+    /// assert!(allocator.capacity() >= 4);
+    /// ```
+    pub fn reserve(&mut self, additional: usize)
+    {
+        if self.monotonic
+        {
+            return;
+        }
+
+        let additional = match self.slot_limit()
+        {
+            Some(limit) => additional.min(limit.saturating_sub(self.used_slots())),
+            _ => additional
+        };
+
+        self.active_indices.reserve(additional);
+    }
+
+    /// Reserves extra space for *at least* `additional` more elements,
+    /// returning an error instead of aborting if the space can't be reserved
+    ///
+    /// Fails without reserving anything if `additional` more indices would exceed the maximum capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::ReserveError;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_max_capacity(4);
+    /// assert_eq!(allocator.try_reserve(4), Ok(()));
+    /// assert!(allocator.capacity() >= 4);
+    ///
+    /// assert_eq!(allocator.try_reserve(5), Err(ReserveError::MaxCapacityExceeded { max_capacity: 4 }));
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), ReserveError>
+    {
+        if let Some(limit) = self.slot_limit()
+        {
+            if self.used_slots().saturating_add(additional) > limit
             {
-                Some((index, allocated_index)) if !allocated_index.is_free => return Some(Index { index, generation: allocated_index.generation }),
-                Some((_, _)) => continue,
-                _ => return None
+                return Err(ReserveError::MaxCapacityExceeded { max_capacity: limit });
             }
         }
+
+        if self.monotonic
+        {
+            return Ok(());
+        }
+
+        self.active_indices.try_reserve(additional)?;
+        Ok(())
+    }
+
+    /// Number of slots counted against the maximum capacity
+    ///
+    /// Monotonic allocators use up a slot with every allocation
+    fn used_slots(&self) -> usize
+    {
+        if self.monotonic
+        {
+            self.next_slot
+        }
+        else
+        {
+            self.active_indices.len()
+        }
+    }
+
+    /// Number of slots that can be used, bounded by both the maximum capacity and the slot range
+    fn slot_limit(&self) -> Option<usize>
+    {
+        let range = if self.range_end == usize::MAX { None } else { Some(self.range_end - self.offset) };
+        match (self.max_capacity, range)
+        {
+            (Some(max_capacity), Some(range)) => Some(max_capacity.min(range)),
+            (max_capacity, range) => max_capacity.or(range)
+        }
+    }
+
+    /// Returns if `index` is still active and hasn't been deallocated
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// assert!(allocator.is_active(index));
+    /// allocator.deallocate(index);
+    /// assert!(!allocator.is_active(index));
+    /// ```
+    pub fn is_active(&self, index: K) -> bool
+    {
+        let index = Index::from_key(index);
+        match self.slot(index).and_then(|slot| self.allocated(slot))
+        {
+            Some(allocated_index) => allocated_index.generation == index.generation && !allocated_index.is_free(),
+            _ => false
+        }
+    }
+
+    /// Returns the number of free indices waiting to be allocated and reused
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.num_free(), 0);
+    ///
+    /// let index: Index = allocator.allocate();
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.num_free(), 1);
+    ///
+    /// let index: Index = allocator.allocate();
+    /// assert_eq!(allocator.num_free(), 0);
+    /// ```
+    pub fn num_free(&self) -> usize
+    {
+        self.free_list.len
+    }
+
+    /// Returns the number of active indices
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// assert_eq!(allocator.num_active(), 0);
+    ///
+    /// let index: Index = allocator.allocate();
+    /// assert_eq!(allocator.num_active(), 1);
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(allocator.num_active(), 0);
+    /// ```
+    pub fn num_active(&self) -> usize
+    {
+        if self.monotonic
+        {
+            return self.live.len();
+        }
+
+        self.active_indices.len()
+            .saturating_sub(self.free_list.len)
+            .saturating_sub(self.quarantined.len())
+            .saturating_sub(self.retired_slots)
+    }
+
+    /// Returns a snapshot of allocation totals, fragmentation and memory usage
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, Stats};
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// allocator.allocate();
+    /// allocator.deallocate(index);
+    ///
+    /// let stats: Stats = allocator.stats();
+    /// assert_eq!(stats.total_allocations, 2);
+    /// assert_eq!(stats.total_frees, 1);
+    /// assert_eq!(stats.peak_active, 2);
+    /// assert_eq!(stats.free_list_len, 1);
+    /// assert_eq!(stats.fragmentation, 0.5);
+    /// ```
+    pub fn stats(&self) -> Stats
+    {
+        // Monotonic allocators have used every slot up to `next_slot`
+        let slots = if self.monotonic { self.next_slot } else { self.active_indices.len() };
+        let fragmentation = if slots == 0
+        {
+            1.0
+        }
+        else
+        {
+            self.num_active() as f64 / slots as f64
+        };
+
+        Stats
+        {
+            total_allocations: self.total_allocations,
+            total_frees: self.total_frees,
+            peak_active: self.peak_active,
+            max_generation: self.slots().map(|(_, allocated_index)| allocated_index.generation).max().unwrap_or(0),
+            retired_slots: self.retired_slots,
+            free_list_len: self.free_list.len,
+            quarantined: self.quarantined.len(),
+            fragmentation,
+            items_bytes: 0,
+            active_indices_bytes: self.active_indices.capacity() * mem::size_of::<AllocatedIndex>()
+                                + self.live.len() * mem::size_of::<(usize, AllocatedIndex)>()
+                                + self.occupied.heap_bytes(),
+            free_indices_bytes: self.quarantined.capacity() * mem::size_of::<QuarantinedSlot>()
+                              + self.freed_generations.len() * mem::size_of::<(usize, usize)>()
+                              + self.skipped.heap_bytes()
+        }
+    }
+
+    /// Returns every index that hasn't been deallocated along with where it was allocated
+    ///
+    /// Intended to be called at teardown to find handles that were never cleaned up.
+    /// Only available with the `leak-detection` feature
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, Leak};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// allocator.allocate();
+    /// allocator.deallocate(index);
+    ///
+    /// for leak in allocator.report_leaks()
+    /// {
+    ///     println!("{}", leak);
+    /// }
+    /// assert_eq!(allocator.report_leaks().len(), 1);
+    /// ```
+    #[cfg(feature = "leak-detection")]
+    pub fn report_leaks(&self) -> Vec<Leak<K>>
+    {
+        self.slots()
+            .filter(|(_, allocated_index)| !allocated_index.is_free())
+            .filter_map(|(index, allocated_index)|
+                {
+                    allocated_index.location.map(|location| Leak
+                    {
+                        index: K::from_raw_parts(self.offset + index, allocated_index.generation),
+                        location
+                    })
+                })
+            .collect()
+    }
+
+    /// Returns an iterator over an immutable `IndexAllocator`
+    /// Each step returns an `Index`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// allocator.allocate();
+    /// allocator.allocate();
+    ///
+    /// for index in allocator
+    /// {
+    ///     println!("{:?}", index);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K>
+    {
+        let slots = if self.monotonic
+        {
+            ActiveSlots::Sparse(self.live.iter())
+        }
+        else
+        {
+            ActiveSlots::Dense(self.occupied.iter())
+        };
+        Iter
+        {
+            allocator: self,
+            slots
+        }
+    }
+
+    /// Returns the bitset of the allocator's active slots, or `None` for a monotonic allocator,
+    /// which only tracks its live indices
+    ///
+    /// Slots are numbered like the `index` of the `Index`es handed out, even if the allocator's
+    /// slot range doesn't start at 0
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::with_range(10..20);
+    /// let index: Index = allocator.allocate();
+    /// allocator.allocate();
+    /// allocator.deallocate(index);
+    ///
+    /// let bits: Vec<usize> = allocator.occupied_bits().map(|bits| bits.iter().collect()).unwrap_or_default();
+    /// assert_eq!(bits, vec![11]);
+    /// assert!(IndexAllocator::monotonic().occupied_bits().is_none());
+    /// ```
+    pub fn occupied_bits(&self) -> Option<&OccupancyBits>
+    {
+        if self.monotonic
+        {
+            None
+        }
+        else
+        {
+            Some(&self.occupied)
+        }
+    }
+
+    /// First active index at or after the slot numbered `from`, along with its generation
+    pub(crate) fn next_active(&self, from: usize) -> Option<(usize, usize)>
+    {
+        if self.monotonic
+        {
+            // Every live index of a monotonic allocator is active
+            let (slot, allocated_index) = self.live.range(from.saturating_sub(self.offset)..).next()?;
+            return Some((self.offset + slot, allocated_index.generation));
+        }
+
+        let index = self.occupied.next_occupied(from)?;
+        Some((index, self.active_generation(index - self.offset)))
+    }
+
+    /// Last active index before the slot numbered `before`, along with its generation
+    pub(crate) fn prev_active(&self, before: usize) -> Option<(usize, usize)>
+    {
+        if self.monotonic
+        {
+            let (slot, allocated_index) = self.live.range(..before.checked_sub(self.offset)?).next_back()?;
+            return Some((self.offset + slot, allocated_index.generation));
+        }
+
+        let index = self.occupied.prev_occupied(before)?;
+        Some((index, self.active_generation(index - self.offset)))
+    }
+
+    /// Generation of the index allocated at the slot numbered `index`, or of the last one allocated there
+    pub(crate) fn generation_at(&self, index: usize) -> usize
+    {
+        let slot = index.checked_sub(self.offset);
+        match slot.and_then(|slot| self.allocated(slot))
+        {
+            Some(allocated_index) => allocated_index.generation,
+            // Monotonic allocators forget freed slots, except for generations set by `allocate_at`
+            _ => slot.and_then(|slot| self.freed_generations.get(&slot)).copied().unwrap_or(0)
+        }
+    }
+
+    /// Generation of the active index at `slot`, counted from the start of the slot range
+    fn active_generation(&self, slot: usize) -> usize
+    {
+        match self.allocated(slot)
+        {
+            Some(allocated_index) => allocated_index.generation,
+            _ => panic!("Occupied slot {} isn't tracked", self.offset + slot)
+        }
+    }
+}
+
+/// Tracked slots of an `IndexAllocator` along with their allocation state
+#[derive(Debug)]
+enum Slots<'a>
+{
+    Dense(iter::Enumerate<slice::Iter<'a, AllocatedIndex>>),
+    Sparse(btree_map::Iter<'a, usize, AllocatedIndex>)
+}
+
+impl<'a> Iterator for Slots<'a>
+{
+    type Item = (usize, &'a AllocatedIndex);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        match self
+        {
+            Slots::Dense(internal) => internal.next(),
+            Slots::Sparse(internal) => internal.next().map(|(slot, allocated_index)| (*slot, allocated_index))
+        }
+    }
+}
+
+/// Struct for consuming a `IndexAllocator` into an iterator
+#[derive(Debug)]
+pub struct IntoIter<K = Index>
+{
+    allocator: IndexAllocator<K>,
+    next: usize
+}
+
+impl<K: Key> Iterator for IntoIter<K>
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        let (index, generation) = self.allocator.next_active(self.next)?;
+        self.next = index + 1;
+        Some(K::from_raw_parts(index, generation))
     }
 }
 
-impl IntoIterator for IndexAllocator
+impl<K: Key> IntoIterator for IndexAllocator<K>
 {
-    type Item = Index;
-    type IntoIter = IntoIter;
+    type Item = K;
+    type IntoIter = IntoIter<K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
         IntoIter
         {
-            internal: self.active_indices.into_iter().enumerate()
+            allocator: self,
+            next: 0
         }
     }
 }
 
+/// Active slots visited by an `Iter`
+#[derive(Debug)]
+enum ActiveSlots<'a>
+{
+    Dense(Ones<'a>),
+    Sparse(btree_map::Iter<'a, usize, AllocatedIndex>)
+}
+
 /// Struct for creating an iterator over an immutable `IndexAllocator` reference
 #[derive(Debug)]
-pub struct Iter<'a>
+pub struct Iter<'a, K = Index>
 {
-    internal: iter::Enumerate<slice::Iter<'a, AllocatedIndex>>
+    allocator: &'a IndexAllocator<K>,
+    slots: ActiveSlots<'a>
 }
 
-impl<'a> Iterator for Iter<'a>
+impl<'a, K: Key> Iterator for Iter<'a, K>
 {
-    type Item = Index;
+    type Item = K;
 
     fn next(&mut self) -> Option<Self::Item>
     {
-        loop
+        let offset = self.allocator.offset;
+        match &mut self.slots
         {
-            loop
-            {
-                match self.internal.next()
+            ActiveSlots::Dense(internal) =>
                 {
-                    Some((index, allocated_index)) if !allocated_index.is_free => return Some(Index { index, generation: allocated_index.generation }),
-                    Some((_, _)) => continue,
-                    _ => return None
-                }
-            }
+                    let index = internal.next()?;
+                    Some(K::from_raw_parts(index, self.allocator.active_indices[index - offset].generation))
+                },
+            // Every live index of a monotonic allocator is active
+            ActiveSlots::Sparse(internal) => internal.next().map(|(slot, allocated_index)| K::from_raw_parts(offset + slot, allocated_index.generation))
         }
     }
 }
 
-impl<'a> IntoIterator for &'a IndexAllocator
+impl<'a, K: Key> IntoIterator for &'a IndexAllocator<K>
 {
-    type Item = Index;
-    type IntoIter = Iter<'a>;
+    type Item = K;
+    type IntoIter = Iter<'a, K>;
 
     fn into_iter(self) -> Self::IntoIter
     {
@@ -389,8 +2015,9 @@ impl<'a> IntoIterator for &'a IndexAllocator
 #[cfg(test)]
 mod allocator_tests
 {
+    use super::{AllocatedIndex, MAX_SLOTS};
     use crate::exposed::*;
-    use crate::Index;
+    use crate::{Index, AllocateAtError, ReserveError};
 
     #[test]
     fn allocate()
@@ -406,17 +2033,117 @@ mod allocator_tests
     }
 
     #[test]
-    fn deallocate()
+    fn deallocate()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        allocator.allocate();
+
+        allocator.deallocate(index);
+
+        let index = allocator.allocate();
+        assert_eq!(index.index, 0);
+        assert_eq!(index.generation, 1);
+    }
+
+    #[test]
+    fn deallocation()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        assert_eq!(allocator.deallocate(index), Deallocation::Freed);
+        assert_eq!(allocator.deallocate(index), Deallocation::AlreadyFree);
+        assert_eq!(allocator.num_free(), 1);
+
+        let index1 = allocator.allocate();
+        assert_eq!(allocator.deallocate(index), Deallocation::Stale);
+        assert_eq!(allocator.deallocate(Index { index: 5, generation: 0 }), Deallocation::Stale);
+        assert!(allocator.is_active(index1));
+        assert_eq!(allocator.num_active(), 1);
+        assert_eq!(allocator.num_free(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Double free")]
+    fn strict_double_free()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.set_strict(true);
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        allocator.deallocate(index);
+    }
+
+    #[test]
+    #[should_panic(expected = "Deallocating stale")]
+    fn strict_stale()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.set_strict(true);
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        allocator.allocate();
+        allocator.deallocate(index);
+    }
+
+    #[test]
+    fn no_duplicate_free_indices()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        allocator.allocate();
+        allocator.deallocate(index);
+        allocator.deallocate(index);
+        allocator.reserve(4);
+        allocator.deallocate_all();
+        allocator.deallocate_all();
+        assert_eq!(allocator.num_free(), 2);
+
+        let mut free: Vec<usize> = allocator.free_slots().collect();
+        free.sort_unstable();
+        free.dedup();
+        assert_eq!(free.len(), allocator.num_free());
+
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+        assert_ne!(index.index, index1.index);
+        assert_eq!(allocator.allocate(), Index { index: 2, generation: 0 });
+    }
+
+    #[test]
+    fn free_list_order()
     {
         let mut allocator = IndexAllocator::new();
-        let index = allocator.allocate();
-        allocator.allocate();
+        let indices: Vec<Index> = (0..5).map(|_| allocator.allocate()).collect();
+        for index in &indices
+        {
+            allocator.deallocate(*index);
+        }
 
-        allocator.deallocate(index);
+        // Unlinking from the middle and the back keeps the remaining slots in order
+        assert_eq!(allocator.allocate_at(Index { index: 2, generation: 1 }), Ok(()));
+        assert_eq!(allocator.allocate_at(Index { index: 4, generation: 1 }), Ok(()));
+        assert_eq!(allocator.free_slots().collect::<Vec<usize>>(), vec![0, 1, 3]);
+        assert_eq!(allocator.num_free(), 3);
 
-        let index = allocator.allocate();
-        assert_eq!(index.index, 0);
-        assert_eq!(index.generation, 1);
+        let mut other = allocator.split_off(1);
+        assert_eq!(allocator.free_slots().collect::<Vec<usize>>(), vec![0]);
+        assert_eq!(other.free_slots().collect::<Vec<usize>>(), vec![0, 2]);
+
+        assert_eq!(other.allocate(), Index { index: 1, generation: 1 });
+        assert_eq!(other.allocate(), Index { index: 3, generation: 1 });
+        assert_eq!(other.num_free(), 0);
+        other.deallocate(Index { index: 2, generation: 1 });
+        assert_eq!(other.allocate(), Index { index: 2, generation: 2 });
+        assert_eq!(allocator.allocate(), Index { index: 0, generation: 1 });
+    }
+
+    #[test]
+    #[cfg(all(target_pointer_width = "64", not(feature = "leak-detection")))]
+    fn allocated_index_size()
+    {
+        // The free list links take the place of an `is_free` flag and its padding
+        assert_eq!(std::mem::size_of::<AllocatedIndex>(), std::mem::size_of::<(bool, usize)>());
     }
 
     #[test]
@@ -452,6 +2179,79 @@ mod allocator_tests
         assert!(allocator.capacity() >= 5);
     }
 
+    #[test]
+    fn max_capacity()
+    {
+        let mut allocator = IndexAllocator::with_max_capacity(2);
+        let index = allocator.try_allocate().expect("Should have room");
+        allocator.try_allocate().expect("Should have room");
+
+        let error = allocator.try_allocate().expect_err("Should be full");
+        assert_eq!(error.max_capacity(), 2);
+        assert_eq!(allocator.num_active(), 2);
+
+        allocator.deallocate(index);
+        let index = allocator.try_allocate().expect("Freed index should be reused");
+        assert_eq!(index.index, 0);
+        assert_eq!(index.generation, 1);
+
+        allocator.reserve(10);
+        assert!(allocator.try_allocate().is_err());
+    }
+
+    #[test]
+    fn max_capacity_with_quarantine()
+    {
+        let mut allocator = IndexAllocator::with_max_capacity(2);
+        allocator.set_quarantine(Some(Quarantine::Ticks(1)));
+        let index = allocator.allocate();
+        allocator.allocate();
+        allocator.deallocate(index);
+        assert_eq!(allocator.num_active(), 1);
+
+        // The quarantined slot still counts against the maximum capacity
+        let error = allocator.try_allocate().expect_err("Freed slot should be quarantined");
+        assert_eq!(error.max_capacity(), 2);
+        assert_eq!(error.quarantined(), 1);
+        assert_eq!(error.to_string(), "maximum capacity of 2 reached with 1 freed slots still in quarantine");
+
+        allocator.advance();
+        assert_eq!(allocator.try_allocate().map(|index: Index| index.index), Ok(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "maximum capacity of 0 reached")]
+    fn allocate_past_max_capacity()
+    {
+        let mut allocator = IndexAllocator::with_max_capacity(0);
+        allocator.allocate();
+    }
+
+    #[test]
+    fn try_reserve()
+    {
+        let mut allocator = IndexAllocator::new();
+        assert_eq!(allocator.try_reserve(3), Ok(()));
+        assert!(allocator.capacity() >= 3);
+
+        let mut allocator = IndexAllocator::with_max_capacity(3);
+        allocator.allocate();
+        assert_eq!(allocator.try_reserve(3), Err(ReserveError::MaxCapacityExceeded { max_capacity: 3 }));
+        assert_eq!(allocator.try_reserve(2), Ok(()));
+
+        // Freed slots still count against the maximum capacity, like they do for reserve
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        assert_eq!(allocator.try_reserve(2), Err(ReserveError::MaxCapacityExceeded { max_capacity: 3 }));
+        assert_eq!(allocator.try_reserve(1), Ok(()));
+
+        match IndexAllocator::new().try_reserve(usize::MAX)
+        {
+            Err(ReserveError::AllocFailed(_)) => (),
+            result => panic!("Reserving usize::MAX should fail to allocate: {:?}", result)
+        }
+    }
+
     #[test]
     fn active()
     {
@@ -467,6 +2267,442 @@ mod allocator_tests
         assert_eq!(allocator.num_free(), 1);
     }
 
+    #[test]
+    fn stats()
+    {
+        let mut allocator = IndexAllocator::new();
+        let stats = allocator.stats();
+        assert_eq!(stats.total_allocations, 0);
+        assert_eq!(stats.fragmentation, 1.0);
+
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+        allocator.deallocate(indices[0]);
+        allocator.deallocate(indices[1]);
+        allocator.deallocate(indices[1]);
+        let index = allocator.allocate();
+
+        let stats = allocator.stats();
+        assert_eq!(stats.total_allocations, 5);
+        assert_eq!(stats.total_frees, 2);
+        assert_eq!(stats.peak_active, 4);
+        assert_eq!(stats.max_generation, 1);
+        assert_eq!(stats.retired_slots, 0);
+        assert_eq!(stats.free_list_len, 1);
+        assert_eq!(stats.fragmentation, 0.75);
+        assert_eq!(stats.items_bytes, 0);
+        assert!(stats.active_indices_bytes >= 4 * std::mem::size_of::<usize>());
+        // The free list lives in the slots themselves
+        assert_eq!(stats.free_indices_bytes, 0);
+
+        allocator.deallocate_all();
+        let stats = allocator.stats();
+        assert_eq!(stats.total_frees, 5);
+        assert_eq!(stats.free_list_len, 4);
+        assert_eq!(stats.fragmentation, 0.0);
+        assert!(!allocator.is_active(index));
+    }
+
+    #[test]
+    fn retire_exhausted_slot()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.allocate();
+        allocator.active_indices[0].generation = usize::MAX;
+        allocator.deallocate(Index { index: 0, generation: usize::MAX });
+
+        assert_eq!(allocator.num_active(), 0);
+        assert_eq!(allocator.num_free(), 0);
+        assert_eq!(allocator.stats().retired_slots, 1);
+        assert_eq!(allocator.stats().max_generation, usize::MAX);
+
+        let index = allocator.allocate();
+        assert_eq!(index, Index { index: 1, generation: 0 });
+    }
+
+    #[test]
+    #[cfg(feature = "leak-detection")]
+    fn report_leaks()
+    {
+        let mut allocator = IndexAllocator::new();
+        assert!(allocator.report_leaks().is_empty());
+
+        let index = allocator.allocate();
+        let (index1, line) = (allocator.try_allocate().expect("Should have room"), line!());
+        allocator.deallocate(index);
+
+        let leaks = allocator.report_leaks();
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].index, index1);
+        assert_eq!(leaks[0].location.file(), file!());
+        assert_eq!(leaks[0].location.line(), line);
+
+        let (index, line) = (allocator.allocate(), line!());
+        let leaks = allocator.report_leaks();
+        assert_eq!(leaks.len(), 2);
+        assert_eq!(leaks[0].index, index);
+        assert_eq!(leaks[0].location.line(), line);
+
+        allocator.deallocate_all();
+        assert!(allocator.report_leaks().is_empty());
+    }
+
+    #[test]
+    fn with_range()
+    {
+        let mut allocator = IndexAllocator::with_range(10..12);
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+        assert_eq!(index, Index { index: 10, generation: 0 });
+        assert_eq!(index1, Index { index: 11, generation: 0 });
+        assert!(allocator.try_allocate().is_err());
+
+        assert!(allocator.is_active(index));
+        assert!(!allocator.is_active(Index { index: 0, generation: 0 }));
+        assert_eq!(allocator.deallocate(Index { index: 0, generation: 0 }), Deallocation::Stale);
+
+        assert_eq!(allocator.deallocate(index), Deallocation::Freed);
+        assert_eq!(allocator.allocate(), Index { index: 10, generation: 1 });
+        assert_eq!(allocator.iter().collect::<Vec<Index>>(), vec![Index { index: 10, generation: 1 }, index1]);
+        assert_eq!(allocator.into_iter().count(), 2);
+    }
+
+    #[test]
+    fn split_off()
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+        allocator.deallocate(indices[1]);
+        allocator.deallocate(indices[3]);
+
+        let mut other = allocator.split_off(2);
+        assert_eq!(allocator.slot_range(), 0..2);
+        assert_eq!(other.slot_range(), 2..2 + MAX_SLOTS);
+
+        assert_eq!(allocator.num_active(), 1);
+        assert_eq!(allocator.num_free(), 1);
+        assert_eq!(other.num_active(), 1);
+        assert_eq!(other.num_free(), 1);
+        assert!(allocator.is_active(indices[0]));
+        assert!(other.is_active(indices[2]));
+        assert!(!allocator.is_active(indices[2]));
+
+        assert_eq!(allocator.allocate(), Index { index: 1, generation: 1 });
+        assert!(allocator.try_allocate().is_err());
+        assert_eq!(allocator.max_capacity(), None);
+        assert_eq!(other.allocate(), Index { index: 3, generation: 1 });
+        assert_eq!(other.allocate(), Index { index: 4, generation: 0 });
+
+        let mut third = other.split_off(10);
+        assert_eq!(third.allocate(), Index { index: 10, generation: 0 });
+        assert_eq!(other.slot_range(), 2..10);
+        assert_eq!(other.max_capacity(), None);
+        assert_eq!(other.deallocate(indices[2]), Deallocation::Freed);
+    }
+
+    #[test]
+    fn range_and_max_capacity()
+    {
+        let mut allocator = IndexAllocator::with_range(10..20);
+        allocator.set_max_capacity(Some(2));
+        assert_eq!(allocator.slot_range(), 10..20);
+        assert_eq!(allocator.max_capacity(), Some(2));
+
+        allocator.allocate();
+        allocator.allocate();
+        assert_eq!(allocator.try_allocate().unwrap_err().max_capacity(), 2);
+        assert_eq!(allocator.allocate_at(Index { index: 15, generation: 0 }),
+                   Err(AllocateAtError::MaxCapacityExceeded { max_capacity: 2 }));
+
+        let mut allocator = IndexAllocator::with_max_capacity(3);
+        let mut other = allocator.split_off(1);
+        assert_eq!(allocator.slot_range(), 0..1);
+        assert_eq!(other.slot_range(), 1..1 + MAX_SLOTS);
+        assert_eq!(other.max_capacity(), Some(3));
+        assert_eq!((0..3).map(|_| other.allocate().index).collect::<Vec<usize>>(), vec![1, 2, 3]);
+        assert!(other.try_allocate().is_err());
+    }
+
+    #[test]
+    fn high_slot_range()
+    {
+        let mut allocator = IndexAllocator::with_range(1 << 40..(1 << 40) + 10);
+        let index = allocator.allocate();
+        assert_eq!(index, Index { index: 1 << 40, generation: 0 });
+        assert!(allocator.stats().active_indices_bytes <= 1024);
+
+        let mut other = IndexAllocator::new().split_off(1 << 41);
+        let index1 = other.allocate();
+        assert_eq!(index1, Index { index: 1 << 41, generation: 0 });
+        assert!(other.stats().active_indices_bytes <= 1024);
+        assert_eq!(other.iter().collect::<Vec<Index>>(), vec![index1]);
+        assert!(other.occupied_bits().is_some_and(|bits| bits.contains(index1.index)));
+    }
+
+    #[test]
+    fn dense_slot_limit()
+    {
+        let mut allocator = IndexAllocator::new();
+        assert_eq!(allocator.slot_range(), 0..MAX_SLOTS);
+        assert_eq!(allocator.allocate_at(Index { index: MAX_SLOTS, generation: 0 }), Err(AllocateAtError::OutOfRange));
+
+        let mut other = allocator.split_off(1 << 40);
+        assert_eq!(allocator.slot_range(), 0..MAX_SLOTS);
+        assert_eq!(other.slot_range(), 1 << 40..(1 << 40) + MAX_SLOTS);
+        assert_eq!(other.allocate_at(Index { index: (1 << 40) + MAX_SLOTS, generation: 0 }), Err(AllocateAtError::OutOfRange));
+
+        let monotonic = IndexAllocator::monotonic();
+        assert_eq!(monotonic.slot_range(), 0..usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of slot range")]
+    fn split_off_out_of_range()
+    {
+        let mut allocator = IndexAllocator::with_range(10..20);
+        allocator.split_off(5);
+    }
+
+    #[test]
+    fn allocate_at()
+    {
+        let mut allocator = IndexAllocator::new();
+        assert_eq!(allocator.allocate_at(Index { index: 3, generation: 2 }), Ok(()));
+        assert_eq!(allocator.num_active(), 1);
+        assert_eq!(allocator.num_free(), 3);
+        assert!(allocator.is_active(Index { index: 3, generation: 2 }));
+
+        // Skipped slots are reused first
+        assert_eq!(allocator.allocate(), Index { index: 0, generation: 1 });
+
+        assert_eq!(allocator.allocate_at(Index { index: 3, generation: 2 }), Err(AllocateAtError::Occupied));
+        assert_eq!(allocator.allocate_at(Index { index: 3, generation: 1 }), Err(AllocateAtError::Stale { current_generation: 2 }));
+
+        // Pulled out of the free list
+        assert_eq!(allocator.allocate_at(Index { index: 1, generation: 0 }), Ok(()));
+        assert_eq!(allocator.num_active(), 3);
+        assert_eq!(allocator.num_free(), 1);
+        assert_eq!(allocator.allocate(), Index { index: 2, generation: 1 });
+        assert_eq!(allocator.allocate(), Index { index: 4, generation: 0 });
+
+        // Replaces an older active generation
+        assert_eq!(allocator.allocate_at(Index { index: 3, generation: 5 }), Ok(()));
+        assert!(!allocator.is_active(Index { index: 3, generation: 2 }));
+        assert_eq!(allocator.num_active(), 5);
+        assert_eq!(allocator.num_free(), 0);
+        assert_eq!(allocator.stats().total_frees, 1);
+        assert_eq!(allocator.deallocate(Index { index: 3, generation: 5 }), Deallocation::Freed);
+    }
+
+    #[test]
+    fn allocate_at_freed()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        assert_eq!(allocator.allocate_at(index), Err(AllocateAtError::Stale { current_generation: 0 }));
+        assert!(!allocator.is_active(index));
+        assert_eq!(allocator.allocate_at(Index { index: 0, generation: 1 }), Ok(()));
+
+        // Slots skipped while growing have never been handed out
+        assert_eq!(allocator.allocate_at(Index { index: 2, generation: 0 }), Ok(()));
+        assert_eq!(allocator.allocate_at(Index { index: 1, generation: 0 }), Ok(()));
+        allocator.deallocate(Index { index: 1, generation: 0 });
+        assert_eq!(allocator.allocate_at(Index { index: 1, generation: 0 }), Err(AllocateAtError::Stale { current_generation: 0 }));
+
+        // So have slots moved by split_off
+        let mut other = IndexAllocator::new();
+        other.allocate_at(Index { index: 5, generation: 0 }).expect("grows");
+        let mut other = other.split_off(3);
+        assert_eq!(other.allocate_at(Index { index: 4, generation: 0 }), Ok(()));
+    }
+
+    #[test]
+    fn allocate_at_range()
+    {
+        let mut allocator = IndexAllocator::with_range(10..20);
+        assert_eq!(allocator.allocate_at(Index { index: 5, generation: 0 }), Err(AllocateAtError::OutOfRange));
+        assert_eq!(allocator.allocate_at(Index { index: 20, generation: 0 }), Err(AllocateAtError::OutOfRange));
+        assert_eq!(allocator.allocate_at(Index { index: 12, generation: 0 }), Ok(()));
+        assert_eq!(allocator.num_free(), 2);
+        assert_eq!(allocator.allocate(), Index { index: 10, generation: 1 });
+    }
+
+    #[test]
+    fn quarantine_ticks()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.set_quarantine(Some(Quarantine::Ticks(2)));
+
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+        allocator.deallocate(index);
+        allocator.advance();
+        allocator.deallocate(index1);
+        assert_eq!(allocator.num_quarantined(), 2);
+        assert_eq!(allocator.num_free(), 0);
+        assert_eq!(allocator.num_active(), 0);
+        assert_eq!(allocator.deallocate(index), Deallocation::AlreadyFree);
+
+        assert_eq!(allocator.allocate(), Index { index: 2, generation: 0 });
+        allocator.advance();
+        assert_eq!(allocator.num_quarantined(), 1);
+        assert_eq!(allocator.allocate(), Index { index: 0, generation: 1 });
+        assert_eq!(allocator.allocate(), Index { index: 3, generation: 0 });
+        allocator.advance();
+        assert_eq!(allocator.allocate(), Index { index: 1, generation: 1 });
+        assert_eq!(allocator.stats().quarantined, 0);
+    }
+
+    #[test]
+    fn quarantine_frees()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.set_quarantine(Some(Quarantine::Frees(2)));
+
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+        allocator.deallocate(indices[0]);
+        allocator.deallocate(indices[1]);
+        assert_eq!(allocator.num_quarantined(), 2);
+        allocator.deallocate(indices[2]);
+        assert_eq!(allocator.num_quarantined(), 2);
+        assert_eq!(allocator.num_free(), 1);
+        assert_eq!(allocator.allocate(), Index { index: 0, generation: 1 });
+
+        allocator.set_quarantine(None);
+        assert_eq!(allocator.num_quarantined(), 0);
+        assert_eq!(allocator.num_free(), 2);
+        assert_eq!(allocator.num_active(), 2);
+    }
+
+    #[test]
+    fn quarantine_allocate_at_and_split()
+    {
+        let mut allocator = IndexAllocator::new();
+        allocator.set_quarantine(Some(Quarantine::Frees(10)));
+
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+        for index in &indices
+        {
+            allocator.deallocate(*index);
+        }
+        assert_eq!(allocator.allocate_at(Index { index: 1, generation: 1 }), Ok(()));
+        assert_eq!(allocator.num_quarantined(), 3);
+        assert_eq!(allocator.stats().retired_slots, 0);
+
+        let mut other = allocator.split_off(2);
+        assert_eq!(allocator.num_quarantined(), 1);
+        assert_eq!(other.num_quarantined(), 2);
+        assert_eq!(other.quarantine(), Some(Quarantine::Frees(10)));
+
+        other.set_quarantine(None);
+        assert_eq!(other.allocate(), Index { index: 2, generation: 1 });
+    }
+
+    #[test]
+    fn monotonic()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        let indices: Vec<Index> = (0..3).map(|_| allocator.allocate()).collect();
+        assert_eq!(allocator.deallocate(indices[1]), Deallocation::Freed);
+        assert_eq!(allocator.deallocate(indices[1]), Deallocation::AlreadyFree);
+        assert_eq!(allocator.deallocate(Index { index: 7, generation: 0 }), Deallocation::Stale);
+        assert_eq!(allocator.deallocate(Index { index: 1, generation: 1 }), Deallocation::Stale);
+        assert_eq!(allocator.num_active(), 2);
+        assert_eq!(allocator.num_free(), 0);
+
+        // Freed slots are never handed out again
+        assert_eq!(allocator.allocate(), Index { index: 3, generation: 0 });
+        assert!(!allocator.is_active(indices[1]));
+        assert_eq!(allocator.iter().collect::<Vec<Index>>(), vec![indices[0], indices[2], Index { index: 3, generation: 0 }]);
+
+        let stats = allocator.stats();
+        assert_eq!(stats.total_allocations, 4);
+        assert_eq!(stats.total_frees, 1);
+        assert_eq!(stats.fragmentation, 0.75);
+
+        allocator.deallocate_all();
+        assert_eq!(allocator.num_active(), 0);
+        assert_eq!(allocator.stats().total_frees, 4);
+        assert_eq!(allocator.allocate(), Index { index: 4, generation: 0 });
+    }
+
+    #[test]
+    fn monotonic_deallocate_allocated_at()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        let index = Index { index: 5, generation: 3 };
+        allocator.allocate_at(index).expect("Slot should be untouched");
+        assert_eq!(allocator.deallocate(index), Deallocation::Freed);
+
+        // Only the generation the slot was freed at is a double free
+        assert_eq!(allocator.deallocate(index), Deallocation::AlreadyFree);
+        assert_eq!(allocator.deallocate(Index { index: 5, generation: 2 }), Deallocation::Stale);
+        assert_eq!(allocator.deallocate(Index { index: 5, generation: 0 }), Deallocation::Stale);
+
+        // Which costs memory for every such slot freed, unlike slots freed at generation 0
+        assert_eq!(allocator.stats().free_indices_bytes, std::mem::size_of::<(usize, usize)>());
+
+        let index1 = Index { index: 6, generation: 1 };
+        allocator.allocate_at(index1).expect("Slot should be untouched");
+        let mut other = allocator.split_off(6);
+        other.deallocate_all();
+        assert_eq!(other.deallocate(index1), Deallocation::AlreadyFree);
+        assert_eq!(other.deallocate(Index { index: 6, generation: 0 }), Deallocation::Stale);
+        assert_eq!(allocator.deallocate(index), Deallocation::AlreadyFree);
+    }
+
+    #[test]
+    fn monotonic_memory()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        for _ in 0..1000
+        {
+            let index = allocator.allocate();
+            allocator.deallocate(index);
+        }
+        allocator.allocate();
+
+        let stats = allocator.stats();
+        assert_eq!(stats.active_indices_bytes, std::mem::size_of::<(usize, AllocatedIndex)>());
+        assert_eq!(stats.free_indices_bytes, 0);
+        assert_eq!(allocator.capacity(), 0);
+    }
+
+    #[test]
+    fn monotonic_allocate_at()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        assert_eq!(allocator.allocate_at(Index { index: 2, generation: 0 }), Ok(()));
+        assert_eq!(allocator.allocate_at(Index { index: 2, generation: 0 }), Err(AllocateAtError::Occupied));
+
+        // Skipped slots are never allocated
+        assert_eq!(allocator.allocate(), Index { index: 3, generation: 0 });
+        assert_eq!(allocator.allocate_at(Index { index: 0, generation: 0 }), Err(AllocateAtError::Retired));
+
+        allocator.deallocate(Index { index: 2, generation: 0 });
+        assert_eq!(allocator.allocate_at(Index { index: 2, generation: 0 }), Err(AllocateAtError::Retired));
+        assert_eq!(allocator.num_active(), 1);
+    }
+
+    #[test]
+    fn monotonic_split_off()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+
+        let mut other = allocator.split_off(2);
+        assert!(other.is_monotonic());
+        assert_eq!(allocator.num_active(), 2);
+        assert_eq!(other.num_active(), 2);
+        assert!(other.is_active(indices[3]));
+        assert!(allocator.try_allocate().is_err());
+        assert_eq!(other.allocate(), Index { index: 4, generation: 0 });
+
+        let mut third = other.split_off(10);
+        assert_eq!(third.allocate(), Index { index: 10, generation: 0 });
+        assert_eq!(other.allocate(), Index { index: 5, generation: 0 });
+    }
+
     #[test]
     fn iter()
     {
@@ -481,4 +2717,65 @@ mod allocator_tests
         assert_eq!(iter.next(), Some(Index { index: 1, generation: 0}));
         assert_eq!(iter.next(), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn occupied_bits()
+    {
+        let mut allocator = IndexAllocator::with_range(100..400);
+        allocator.set_quarantine(Some(Quarantine::Frees(1)));
+        let indices: Vec<Index> = (0..300).map(|_| allocator.allocate()).collect();
+        for index in &indices[..250]
+        {
+            allocator.deallocate(*index);
+        }
+        assert_eq!(allocator.occupied_bits().map(|bits| bits.len()), Some(50));
+
+        let other = allocator.split_off(375);
+        assert_eq!(allocator.iter().collect::<Vec<Index>>(), indices[250..275]);
+        assert_eq!(other.into_iter().collect::<Vec<Index>>(), indices[275..]);
+
+        let reused = allocator.allocate();
+        assert!(allocator.occupied_bits().is_some_and(|bits| bits.contains(reused.index)));
+        allocator.deallocate_all();
+        assert!(allocator.occupied_bits().is_some_and(|bits| bits.is_empty()));
+    }
+
+    #[test]
+    fn subscriptions()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+
+        // Nothing is logged without subscriptions
+        allocator.deallocate(index);
+        let fast = allocator.subscribe();
+        let slow = allocator.subscribe();
+        assert!(allocator.drain_removed(fast).is_empty());
+
+        allocator.deallocate(index1);
+        assert_eq!(allocator.drain_removed(fast), vec![index1]);
+
+        let index2 = allocator.allocate();
+        allocator.deallocate_all();
+        assert_eq!(allocator.drain_removed(fast), vec![index2]);
+        assert_eq!(allocator.drain_removed(slow), vec![index1, index2]);
+        assert!(allocator.removals.removed.is_empty());
+
+        // Implicit frees by allocate_at are logged too
+        let index3 = allocator.allocate();
+        allocator.allocate_at(Index { index: index3.index, generation: index3.generation + 1 }).expect("newer generation");
+        assert!(allocator.unsubscribe(slow));
+        assert_eq!(allocator.drain_removed(fast), vec![index3]);
+        assert!(allocator.removals.removed.is_empty());
+        assert!(allocator.drain_removed(slow).is_empty());
+
+        let mut allocator = IndexAllocator::monotonic();
+        let subscription = allocator.subscribe();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+        allocator.deallocate(index);
+        allocator.deallocate_all();
+        assert_eq!(allocator.drain_removed(subscription), vec![index, index1]);
+    }
+}
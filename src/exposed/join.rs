@@ -0,0 +1,467 @@
+use std::{mem, marker::PhantomData};
+use crate::{Index, Key, OccupancyBits, exposed::{ExposedGenVec, gen_vec::Slot}};
+
+/// Returns an iterator over the indices present in every column of `query`
+///
+/// `query` is a tuple of up to 8 participants, each borrowing an `ExposedGenVec` whose indices come from the
+/// same `IndexAllocator`:
+///
+/// * `&vec` yields `&T` and `&mut vec` yields `&mut T`. The index must hold a value of matching generation
+/// * `Optional(&vec)` or `Optional(&mut vec)` yields `Option<&T>` or `Option<&mut T>` without restricting the join
+/// * `Without(&vec)` yields `()` and skips indices that hold a value in `vec`
+///
+/// Each step returns `(Index, ...)` with one item per participant, in slot order. Only the slots occupied in
+/// every required column are visited, found by intersecting their `occupied_bits`. A query without a required
+/// column yields nothing
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::Index;
+/// use gen_vec::exposed::{join, IndexAllocator, ExposedGenVec, Optional, Without};
+///
+/// let mut allocator: IndexAllocator = IndexAllocator::new();
+/// let player: Index = allocator.allocate();
+/// let rock: Index = allocator.allocate();
+/// let ghost: Index = allocator.allocate();
+///
+/// let mut positions: ExposedGenVec<f32> = ExposedGenVec::new();
+/// let mut velocities: ExposedGenVec<f32> = ExposedGenVec::new();
+/// let mut names: ExposedGenVec<&str> = ExposedGenVec::new();
+/// let mut frozen: ExposedGenVec<()> = ExposedGenVec::new();
+///
+/// positions.set(player, 0.0);
+/// positions.set(rock, 5.0);
+/// positions.set(ghost, 9.0);
+/// velocities.set(player, 1.0);
+/// velocities.set(rock, 1.0);
+/// velocities.set(ghost, 2.0);
+/// names.set(player, "player");
+/// frozen.set(rock, ());
+///
+/// for (_, position, velocity, name, ()) in join((&mut positions, &velocities, Optional(&names), Without(&frozen)))
+/// {
+///     *position += velocity;
+///     println!("{} moved", name.unwrap_or(&"unnamed"));
+/// }
+///
+/// assert_eq!(positions.get(player), Some(&1.0));
+/// assert_eq!(positions.get(rock), Some(&5.0));
+/// assert_eq!(positions.get(ghost), Some(&11.0));
+/// ```
+pub fn join<K: Key, Q: IntoJoin<K>>(query: Q) -> Join<Q::Fetch, K>
+{
+    query.into_join()
+}
+
+/// Join participant yielding `Option<&T>` or `Option<&mut T>` instead of restricting the join to indices
+/// present in its column. See `join`
+#[derive(Debug)]
+pub struct Optional<P>(pub P);
+
+/// Join participant skipping indices present in its column. See `join`
+#[derive(Debug)]
+pub struct Without<P>(pub P);
+
+/// A participant of a `join` ready to fetch items
+pub trait Fetch
+{
+    /// Item yielded for each index of the join
+    type Item;
+
+    /// Number of slots of a required participant, or `None` if the participant doesn't restrict the join
+    fn bound(&self) -> Option<usize>;
+
+    /// Occupied slots of a required participant, or `None` if the participant doesn't restrict the join
+    fn occupied_bits(&self) -> Option<&OccupancyBits>;
+
+    /// Generation of the value held at `slot`, if any
+    fn generation(&self, slot: usize) -> Option<usize>;
+
+    /// Returns `true` if the participant lets the index at `slot` and `generation` through
+    fn accepts(&self, slot: usize, generation: usize) -> bool;
+
+    /// Fetches the item of an accepted index. Slots are fetched in increasing order
+    fn fetch(&mut self, slot: usize, generation: usize) -> Self::Item;
+}
+
+/// Converts a value into a `join` participant
+pub trait IntoFetch<K>
+{
+    /// The participant
+    type Fetch: Fetch;
+
+    /// Returns the participant
+    fn into_fetch(self) -> Self::Fetch;
+}
+
+/// Converts a tuple of participants into a `Join`
+pub trait IntoJoin<K>
+{
+    /// Tuple of the participants
+    type Fetch;
+
+    /// Returns the `Join` iterator
+    fn into_join(self) -> Join<Self::Fetch, K>;
+}
+
+/// Required participant yielding `&T`
+#[derive(Debug)]
+pub struct Column<'a, T>
+{
+    items: &'a [Slot<T>],
+    occupied: &'a OccupancyBits
+}
+
+/// Required participant yielding `&mut T`
+#[derive(Debug)]
+pub struct ColumnMut<'a, T>
+{
+    items: &'a mut [Slot<T>],
+    offset: usize,
+    occupied: &'a OccupancyBits
+}
+
+impl<'a, T> Fetch for Column<'a, T>
+{
+    type Item = &'a T;
+
+    fn bound(&self) -> Option<usize>
+    {
+        Some(self.items.len())
+    }
+
+    fn occupied_bits(&self) -> Option<&OccupancyBits>
+    {
+        Some(self.occupied)
+    }
+
+    fn generation(&self, slot: usize) -> Option<usize>
+    {
+        self.items.get(slot)?.item().map(|item| item.generation)
+    }
+
+    fn accepts(&self, slot: usize, generation: usize) -> bool
+    {
+        self.generation(slot) == Some(generation)
+    }
+
+    fn fetch(&mut self, slot: usize, _generation: usize) -> &'a T
+    {
+        match self.items[slot].item()
+        {
+            Some(item) => &item.value,
+            _ => panic!("Slot {} shouldn't be fetched while vacant", slot)
+        }
+    }
+}
+
+impl<'a, T> Fetch for ColumnMut<'a, T>
+{
+    type Item = &'a mut T;
+
+    fn bound(&self) -> Option<usize>
+    {
+        Some(self.offset + self.items.len())
+    }
+
+    fn occupied_bits(&self) -> Option<&OccupancyBits>
+    {
+        Some(self.occupied)
+    }
+
+    fn generation(&self, slot: usize) -> Option<usize>
+    {
+        self.items.get(slot.checked_sub(self.offset)?)?.item().map(|item| item.generation)
+    }
+
+    fn accepts(&self, slot: usize, generation: usize) -> bool
+    {
+        self.generation(slot) == Some(generation)
+    }
+
+    fn fetch(&mut self, slot: usize, _generation: usize) -> &'a mut T
+    {
+        // Split off every slot up to and including `slot`, since later fetches only look further ahead
+        let items = mem::take(&mut self.items);
+        let (fetched, rest) = items.split_at_mut(slot - self.offset + 1);
+        self.items = rest;
+        self.offset = slot + 1;
+        match fetched.last_mut().and_then(Slot::item_mut)
+        {
+            Some(item) => &mut item.value,
+            _ => panic!("Slot {} shouldn't be fetched while vacant", slot)
+        }
+    }
+}
+
+impl<F: Fetch> Fetch for Optional<F>
+{
+    type Item = Option<F::Item>;
+
+    fn bound(&self) -> Option<usize>
+    {
+        None
+    }
+
+    fn occupied_bits(&self) -> Option<&OccupancyBits>
+    {
+        None
+    }
+
+    fn generation(&self, slot: usize) -> Option<usize>
+    {
+        self.0.generation(slot)
+    }
+
+    fn accepts(&self, _slot: usize, _generation: usize) -> bool
+    {
+        true
+    }
+
+    fn fetch(&mut self, slot: usize, generation: usize) -> Option<F::Item>
+    {
+        if self.0.accepts(slot, generation)
+        {
+            Some(self.0.fetch(slot, generation))
+        }
+        else
+        {
+            None
+        }
+    }
+}
+
+impl<F: Fetch> Fetch for Without<F>
+{
+    type Item = ();
+
+    fn bound(&self) -> Option<usize>
+    {
+        None
+    }
+
+    fn occupied_bits(&self) -> Option<&OccupancyBits>
+    {
+        None
+    }
+
+    fn generation(&self, slot: usize) -> Option<usize>
+    {
+        self.0.generation(slot)
+    }
+
+    fn accepts(&self, slot: usize, generation: usize) -> bool
+    {
+        !self.0.accepts(slot, generation)
+    }
+
+    fn fetch(&mut self, _slot: usize, _generation: usize) {}
+}
+
+impl<'a, T, K: Key> IntoFetch<K> for &'a ExposedGenVec<T, K>
+{
+    type Fetch = Column<'a, T>;
+
+    fn into_fetch(self) -> Column<'a, T>
+    {
+        Column { items: self.slots(), occupied: self.occupied_bits() }
+    }
+}
+
+impl<'a, T, K: Key> IntoFetch<K> for &'a mut ExposedGenVec<T, K>
+{
+    type Fetch = ColumnMut<'a, T>;
+
+    fn into_fetch(self) -> ColumnMut<'a, T>
+    {
+        let (items, occupied) = self.slots_mut();
+        ColumnMut { items, offset: 0, occupied }
+    }
+}
+
+impl<K, P: IntoFetch<K>> IntoFetch<K> for Optional<P>
+{
+    type Fetch = Optional<P::Fetch>;
+
+    fn into_fetch(self) -> Optional<P::Fetch>
+    {
+        Optional(self.0.into_fetch())
+    }
+}
+
+impl<K, P: IntoFetch<K>> IntoFetch<K> for Without<P>
+{
+    type Fetch = Without<P::Fetch>;
+
+    fn into_fetch(self) -> Without<P::Fetch>
+    {
+        Without(self.0.into_fetch())
+    }
+}
+
+/// Iterator over the indices present in every required column of a `join`
+#[derive(Debug)]
+pub struct Join<F, K = Index>
+{
+    fetch: F,
+    occupied: OccupancyBits,
+    slot: usize,
+    end: usize,
+    key_type: PhantomData<K>
+}
+
+macro_rules! impl_join
+{
+    ($(($name:ident, $fetch:ident)),+) =>
+    {
+        impl<K: Key, $($name: IntoFetch<K>),+> IntoJoin<K> for ($($name,)+)
+        {
+            type Fetch = ($($name::Fetch,)+);
+
+            #[allow(non_snake_case)]
+            fn into_join(self) -> Join<Self::Fetch, K>
+            {
+                let ($($name,)+) = self;
+                let fetch = ($($name.into_fetch(),)+);
+                let ($($fetch,)+) = &fetch;
+                let end = [$($fetch.bound()),+].iter().flatten().copied().min().unwrap_or(0);
+
+                // Only slots occupied in every required column can match
+                let occupied = [$($fetch.occupied_bits()),+].iter()
+                                                             .flatten()
+                                                             .fold(None, |occupied: Option<OccupancyBits>, bits| match occupied
+                                                             {
+                                                                 Some(mut occupied) =>
+                                                                     {
+                                                                         occupied.intersect_with(bits);
+                                                                         Some(occupied)
+                                                                     },
+                                                                 None => Some(OccupancyBits::clone(bits))
+                                                             });
+                Join { fetch, occupied: occupied.unwrap_or_default(), slot: 0, end, key_type: PhantomData }
+            }
+        }
+
+        impl<K: Key, $($name: Fetch),+> Iterator for Join<($($name,)+), K>
+        {
+            type Item = (K, $($name::Item),+);
+
+            fn next(&mut self) -> Option<Self::Item>
+            {
+                let ($($fetch,)+) = &mut self.fetch;
+                let end = self.end;
+                while let Some(slot) = self.occupied.next_occupied(self.slot).filter(|slot| *slot < end)
+                {
+                    self.slot = slot + 1;
+
+                    // The first required participant decides the generation the others must match
+                    let generation = None$(.or_else(| | $fetch.bound().and_then(|_| $fetch.generation(slot))))+;
+                    if let Some(generation) = generation
+                    {
+                        if true $(&& $fetch.accepts(slot, generation))+
+                        {
+                            return Some((K::from_raw_parts(slot, generation), $($fetch.fetch(slot, generation)),+));
+                        }
+                    }
+                }
+                None
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>)
+            {
+                (0, Some(self.end.saturating_sub(self.slot)))
+            }
+        }
+    };
+}
+
+impl_join!((A, a));
+impl_join!((A, a), (B, b));
+impl_join!((A, a), (B, b), (C, c));
+impl_join!((A, a), (B, b), (C, c), (D, d));
+impl_join!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_join!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+impl_join!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+impl_join!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g), (H, h));
+
+#[cfg(test)]
+mod join_tests
+{
+    use crate::Index;
+    use crate::exposed::*;
+
+    #[test]
+    fn matching_generations()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+
+        let mut a = ExposedGenVec::new();
+        let mut b = ExposedGenVec::new();
+        a.set(index, 0);
+        a.set(index1, 1);
+        b.set(index, 10);
+
+        // `b` still holds the old generation of `index`
+        allocator.deallocate(index);
+        let index2 = allocator.allocate();
+        a.set(index2, 2);
+
+        let joined: Vec<(Index, &i32, &mut i32)> = join((&a, &mut b)).collect();
+        assert!(joined.is_empty());
+
+        b.set(index1, 11);
+        let joined: Vec<(Index, &i32, &mut i32)> = join((&a, &mut b)).collect();
+        assert_eq!(joined, vec![(index1, &1, &mut 11)]);
+    }
+
+    #[test]
+    fn optional_and_without()
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..4).map(|_| allocator.allocate()).collect();
+
+        let mut a = ExposedGenVec::new();
+        let mut b = ExposedGenVec::new();
+        let mut c = ExposedGenVec::new();
+        for (value, index) in indices.iter().enumerate()
+        {
+            a.set(*index, value);
+        }
+        b.set(indices[1], 'b');
+        b.set(indices[3], 'd');
+        c.set(indices[2], ());
+
+        let joined: Vec<(Index, &mut usize, Option<&mut char>, ())> =
+            join((&mut a, Optional(&mut b), Without(&c))).collect();
+        assert_eq!(joined, vec![
+            (indices[0], &mut 0, None, ()),
+            (indices[1], &mut 1, Some(&mut 'b'), ()),
+            (indices[3], &mut 3, Some(&mut 'd'), ())
+        ]);
+    }
+
+    #[test]
+    fn shortest_column_bounds_join()
+    {
+        let mut allocator = IndexAllocator::new();
+        let indices: Vec<Index> = (0..100).map(|_| allocator.allocate()).collect();
+
+        let mut a = ExposedGenVec::new();
+        let mut b = ExposedGenVec::new();
+        for index in &indices
+        {
+            a.set(*index, 0);
+        }
+        b.set(indices[1], 0);
+
+        let joined = join((&a, &b));
+        assert_eq!(joined.size_hint(), (0, Some(2)));
+        assert_eq!(joined.count(), 1);
+
+        let only_optional = join((Optional(&a),));
+        assert_eq!(only_optional.count(), 0);
+    }
+}
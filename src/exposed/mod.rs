@@ -19,6 +19,13 @@
 //! ```
 
 pub mod gen_vec;
-pub use self::gen_vec::*;
+pub use self::gen_vec::ExposedGenVec;
 pub mod index_allocator;
-pub use self::index_allocator::*;
\ No newline at end of file
+pub use self::index_allocator::{IndexAllocator, Deallocation, Quarantine, Subscription};
+#[cfg(feature = "leak-detection")]
+pub use self::index_allocator::Leak;
+pub mod sparse_gen_map;
+pub use self::sparse_gen_map::SparseGenMap;
+
+pub mod join;
+pub use self::join::*;
@@ -0,0 +1,1111 @@
+use std::
+{
+    mem,
+    marker::PhantomData,
+    collections::{HashMap, hash_map, TryReserveError}
+};
+use crate::{Index, Key, IndexError, Item, SetError, TransferError, exposed::IndexAllocator};
+
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Generationally indexed map that relies on an independent `IndexAllocator`
+///
+/// Unlike `ExposedGenVec`, only slots holding a value take up memory, which suits
+/// monotonic allocators and values that only a few indices have. Otherwise it offers the same API,
+/// so storage can be chosen per column. Iteration order is unspecified
+///
+/// Removing a value keeps its slot's generation, so stale indices are still rejected there. Those generations
+/// are dropped once the allocator frees their slots (see `retain_live` and `purge_removed`)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SparseGenMap<T, K = Index>
+{
+    items: HashMap<usize, Item<T>>,
+    /// Generations of removed values, by slot
+    removed: HashMap<usize, usize>,
+    key_type: PhantomData<K>
+}
+
+impl<T, K: Key> Default for SparseGenMap<T, K>
+{
+    fn default() -> SparseGenMap<T, K>
+    {
+        SparseGenMap::with_key()
+    }
+}
+
+impl<T> SparseGenMap<T>
+{
+    /// Returns an empty `SparseGenMap`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// ```
+    pub fn new() -> SparseGenMap<T>
+    {
+        SparseGenMap::with_key()
+    }
+
+    /// Returns a `SparseGenMap` with room for `capacity` values before allocating more space
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    /// let map: SparseGenMap<i32> = SparseGenMap::with_capacity(5);
+    /// assert!(map.capacity() >= 5);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> SparseGenMap<T>
+    {
+        SparseGenMap::with_capacity_and_key(capacity)
+    }
+}
+
+impl<T, K: Key> SparseGenMap<T, K>
+{
+    /// Returns an empty `SparseGenMap` accessed with keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let map: SparseGenMap<i32, EntityId> = SparseGenMap::with_key();
+    /// ```
+    pub fn with_key() -> SparseGenMap<T, K>
+    {
+        SparseGenMap
+        {
+            items: HashMap::new(),
+            removed: HashMap::new(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Returns a `SparseGenMap` with room for `capacity` values accessed with keys of type `K`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::new_key_type;
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// new_key_type! { struct EntityId; }
+    ///
+    /// let map: SparseGenMap<i32, EntityId> = SparseGenMap::with_capacity_and_key(5);
+    /// assert!(map.capacity() >= 5);
+    /// ```
+    pub fn with_capacity_and_key(capacity: usize) -> SparseGenMap<T, K>
+    {
+        SparseGenMap
+        {
+            items: HashMap::with_capacity(capacity),
+            removed: HashMap::new(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Number of values the map can hold without reallocating
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    /// let map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.capacity(), 0);
+    /// ```
+    pub fn capacity(&self) -> usize
+    {
+        self.items.capacity()
+    }
+
+    /// Reserves space for *at least* `additional` more values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.reserve(4);
+    /// assert!(map.capacity() >= 4);
+    /// ```
+    pub fn reserve(&mut self, additional: usize)
+    {
+        self.items.reserve(additional)
+    }
+
+    /// Reserves space for *at least* `additional` more values,
+    /// returning an error instead of aborting if the space can't be reserved
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert!(map.try_reserve(4).is_ok());
+    /// assert!(map.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError>
+    {
+        self.items.try_reserve(additional)
+    }
+
+    /// Approximate number of heap bytes used to store the map's values
+    ///
+    /// Counts one control byte per bucket on top of each stored slot and value,
+    /// as well as the generations kept for removed values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// let map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.heap_bytes(), 0);
+    ///
+    /// let map: SparseGenMap<i32> = SparseGenMap::with_capacity(5);
+    /// assert!(map.heap_bytes() >= 5 * std::mem::size_of::<i32>());
+    /// ```
+    pub fn heap_bytes(&self) -> usize
+    {
+        self.items.capacity() * (mem::size_of::<(usize, Item<T>)>() + 1) +
+        self.removed.capacity() * (mem::size_of::<(usize, usize)>() + 1)
+    }
+
+    /// Number of values in the map
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(allocator.allocate(), 0);
+    /// map.set(allocator.allocate(), 1);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize
+    {
+        self.items.len()
+    }
+
+    /// Returns `true` if the map holds no values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::SparseGenMap;
+    ///
+    /// let map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool
+    {
+        self.items.is_empty()
+    }
+
+    /// Returns `true` if the `index` points to a valid item
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert!(!map.contains(index));
+    /// map.set(index, 0);
+    /// assert!(map.contains(index));
+    /// ```
+    pub fn contains(&self, index: K) -> bool
+    {
+        self.get(index).is_some()
+    }
+
+    /// Set the value for the given `index` and returns the previous value (if any)
+    ///
+    /// This may overwrite past (but not future) generations. If the slot holds a newer generation,
+    /// `index` is stale: `value` is silently dropped and `None` is returned, just like setting an empty slot.
+    /// Use `try_set` to tell the two apart and get `value` back
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    /// assert!(map.contains(index));
+    ///
+    /// let replaced: i32 = map.set(index, 1).expect("0");
+    /// assert_eq!(replaced, 0);
+    /// ```
+    pub fn set(&mut self, index: K, value: T) -> Option<T>
+    {
+        self.try_set(index, value).unwrap_or(None)
+    }
+
+    /// Set the value for the given `index` and returns the previous value (if any),
+    /// or hands `value` back if the slot holds or held a newer generation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// allocator.deallocate(index);
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.try_set(index1, 1), Ok(None));
+    ///
+    /// let error = map.try_set(index, 0).expect_err("index is stale");
+    /// assert_eq!(error.current_generation(), 1);
+    /// assert_eq!(error.into_value(), 0);
+    ///
+    /// // The slot remembers its generation after the value is removed
+    /// map.remove(index1);
+    /// assert!(map.try_set(index, 0).is_err());
+    /// ```
+    pub fn try_set(&mut self, index: K, value: T) -> Result<Option<T>, SetError<T>>
+    {
+        let index = Index::from_key(index);
+        match self.generation(index.index)
+        {
+            Some(current) if current > index.generation => Err(SetError::new(value, current)),
+            _ =>
+                {
+                    self.removed.remove(&index.index);
+                    Ok(self.items.insert(index.index, Item { value, generation: index.generation }).map(|item| item.value))
+                }
+        }
+    }
+
+    /// Removes the value of `index` from the map, keeping only its slot's generation
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    ///
+    /// assert_eq!(map.remove(index), Some(0));
+    /// assert_eq!(map.remove(index), None);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&mut self, index: K) -> Option<T>
+    {
+        self.try_remove(index).ok()
+    }
+
+    /// Removes the value of `index` from the map, or returns why `index` is invalid
+    ///
+    /// Slots without a value are `IndexError::Vacant`, as the map has no bounds
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.try_remove(index), Err(IndexError::Vacant));
+    ///
+    /// map.set(index, 0);
+    /// assert_eq!(map.try_remove(index), Ok(0));
+    /// ```
+    pub fn try_remove(&mut self, index: K) -> Result<T, IndexError>
+    {
+        self.try_get(index)?;
+        let slot = Index::from_key(index).index;
+        let removed = self.items.remove(&slot)
+                                .unwrap_or_else(| | panic!("{:?} shouldn't access a missing slot", index));
+        self.removed.insert(slot, removed.generation);
+        Ok(removed.value)
+    }
+
+    /// Moves the value of `index` into `to` without reallocating `index`,
+    /// returning the value `to` previously held at `index` (if any)
+    ///
+    /// Fails, leaving both maps untouched, if `index` isn't valid in this map or
+    /// if `to` holds a newer generation at the same slot
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut inactive: SparseGenMap<i32> = SparseGenMap::new();
+    /// let mut active: SparseGenMap<i32> = SparseGenMap::new();
+    /// inactive.set(index, 5);
+    ///
+    /// assert_eq!(inactive.transfer(&mut active, index), Ok(None));
+    /// assert!(!inactive.contains(index));
+    /// assert_eq!(active.get(index), Some(&5));
+    /// ```
+    pub fn transfer(&mut self, to: &mut SparseGenMap<T, K>, index: K) -> Result<Option<T>, TransferError>
+    {
+        self.try_get(index).map_err(TransferError::Source)?;
+        let (slot, generation) = index.into_raw_parts();
+        if let Some(current) = to.generation(slot)
+        {
+            if current > generation
+            {
+                return Err(TransferError::DestinationNewer { current_generation: current });
+            }
+        }
+
+        let value = self.try_remove(index).map_err(TransferError::Source)?;
+        Ok(to.set(index, value))
+    }
+
+    /// Moves the values of every index in `indices` into `to` without reallocating them
+    ///
+    /// Returns the result of each transfer in order. See `transfer`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError, TransferError};
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut inactive: SparseGenMap<i32> = SparseGenMap::new();
+    /// let mut active: SparseGenMap<i32> = SparseGenMap::new();
+    /// inactive.set(index, 5);
+    ///
+    /// let results = inactive.transfer_many(&mut active, vec![index, index1]);
+    /// assert_eq!(results, vec![Ok(None), Err(TransferError::Source(IndexError::Vacant))]);
+    /// ```
+    pub fn transfer_many<I>(&mut self, to: &mut SparseGenMap<T, K>, indices: I) -> Vec<Result<Option<T>, TransferError>>
+        where I: IntoIterator<Item = K>
+    {
+        indices.into_iter().map(|index| self.transfer(to, index)).collect()
+    }
+
+    /// Exchanges the values of `a` and `b` in place, leaving both indices pointing to their slots
+    ///
+    /// Fails, leaving the map untouched, with the reason the first invalid index couldn't be used
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let a: Index = allocator.allocate();
+    /// let b: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(a, 0);
+    /// map.set(b, 1);
+    ///
+    /// assert_eq!(map.swap(a, b), Ok(()));
+    /// assert_eq!(map.get(a), Some(&1));
+    /// assert_eq!(map.get(b), Some(&0));
+    /// ```
+    pub fn swap(&mut self, a: K, b: K) -> Result<(), IndexError>
+    {
+        self.try_get(a)?;
+        self.try_get(b)?;
+
+        let (a, b) = (Index::from_key(a).index, Index::from_key(b).index);
+        if a != b
+        {
+            if let Some(mut item) = self.items.remove(&a)
+            {
+                if let Some(other) = self.items.get_mut(&b)
+                {
+                    mem::swap(&mut item.value, &mut other.value);
+                }
+                self.items.insert(a, item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the value of a valid `index`, returning the previous value
+    ///
+    /// Unlike `set`, this never fills an empty slot. If `index` is invalid, `value` is handed back as the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.replace(index, 1), Err(1));
+    ///
+    /// map.set(index, 0);
+    /// assert_eq!(map.replace(index, 1), Ok(0));
+    /// ```
+    pub fn replace(&mut self, index: K, value: T) -> Result<T, T>
+    {
+        match self.get_mut(index)
+        {
+            Some(current) => Ok(mem::replace(current, value)),
+            _ => Err(value)
+        }
+    }
+
+    /// Returns an immutable reference to the value of `index` if `index` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    /// let value: Option<&i32> = map.get(index);
+    /// assert_eq!(value, Some(&0));
+    /// ```
+    pub fn get(&self, index: K) -> Option<&T>
+    {
+        self.try_get(index).ok()
+    }
+
+    /// Returns an immutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// assert_eq!(map.try_get(index), Err(IndexError::Vacant));
+    ///
+    /// map.set(index, 0);
+    /// allocator.deallocate(index);
+    /// let index1: Index = allocator.allocate();
+    /// map.set(index1, 1);
+    /// assert_eq!(map.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+    /// ```
+    pub fn try_get(&self, index: K) -> Result<&T, IndexError>
+    {
+        let index = Index::from_key(index);
+        match self.items.get(&index.index)
+        {
+            Some(item) if item.generation == index.generation => Ok(&item.value),
+            Some(item) => Err(IndexError::mismatch(item.generation, index.generation)),
+            None => Err(vacant(&self.removed, index))
+        }
+    }
+
+    /// Returns a mutable reference to the value of `index` if `index` is valid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    ///
+    /// if let Some(value) = map.get_mut(index)
+    /// {
+    ///     *value = 1;
+    /// }
+    /// assert_eq!(map.get(index), Some(&1));
+    /// ```
+    pub fn get_mut(&mut self, index: K) -> Option<&mut T>
+    {
+        self.try_get_mut(index).ok()
+    }
+
+    /// Returns a mutable reference to the value of `index`, or why `index` is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, IndexError};
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    ///
+    /// if let Ok(value) = map.try_get_mut(index)
+    /// {
+    ///     *value = 1;
+    /// }
+    /// assert_eq!(map.get(index), Some(&1));
+    ///
+    /// map.remove(index);
+    /// assert_eq!(map.try_get_mut(index), Err(IndexError::Vacant));
+    /// ```
+    pub fn try_get_mut(&mut self, index: K) -> Result<&mut T, IndexError>
+    {
+        let index = Index::from_key(index);
+        match self.items.get_mut(&index.index)
+        {
+            Some(item) if item.generation == index.generation => Ok(&mut item.value),
+            Some(item) => Err(IndexError::mismatch(item.generation, index.generation)),
+            None => Err(vacant(&self.removed, index))
+        }
+    }
+
+    /// Returns an immutable reference to the value of `index` if `index` is valid here and still active in `allocator`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(map.get(index), Some(&0));
+    /// assert_eq!(map.get_live(&allocator, index), None);
+    /// ```
+    pub fn get_live(&self, allocator: &IndexAllocator<K>, index: K) -> Option<&T>
+    {
+        if allocator.is_active(index)
+        {
+            self.get(index)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Drops every value whose index is no longer active in `allocator`, returning how many were dropped
+    ///
+    /// The generations kept for removed values are dropped too once their slots are freed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    ///
+    /// allocator.deallocate(index);
+    /// assert_eq!(map.retain_live(&allocator), 1);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn retain_live(&mut self, allocator: &IndexAllocator<K>) -> usize
+    {
+        let len = self.items.len();
+        self.items.retain(|slot, item| allocator.is_active(K::from_raw_parts(*slot, item.generation)));
+        self.removed.retain(|slot, generation| allocator.is_active(K::from_raw_parts(*slot, *generation)));
+        len - self.items.len()
+    }
+
+    /// Drops the values and removed generations of indices the allocator has freed,
+    /// returning how many values were dropped
+    ///
+    /// Feed it what a subscription drains from the allocator so removed slots don't pile up,
+    /// without scanning the whole map like `retain_live`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    /// let subscription = allocator.subscribe();
+    /// let index: Index = allocator.allocate();
+    /// let index1: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 0);
+    /// map.set(index1, 1);
+    /// map.remove(index1);
+    ///
+    /// allocator.deallocate(index);
+    /// allocator.deallocate(index1);
+    /// assert_eq!(map.purge_removed(allocator.drain_removed(subscription)), 1);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn purge_removed<I>(&mut self, removed: I) -> usize
+        where I: IntoIterator<Item = K>
+    {
+        let mut purged = 0;
+        for index in removed
+        {
+            let (slot, generation) = index.into_raw_parts();
+            if self.items.get(&slot).is_some_and(|item| item.generation <= generation)
+            {
+                self.items.remove(&slot);
+                purged += 1;
+            }
+            if self.removed.get(&slot).is_some_and(|current| *current <= generation)
+            {
+                self.removed.remove(&slot);
+            }
+        }
+        purged
+    }
+
+    /// Returns an iterator of immutable references to the map's values in no particular order
+    ///
+    /// Each iterator step returns (Index, &T)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(allocator.allocate(), 0);
+    /// map.set(allocator.allocate(), 1);
+    ///
+    /// for (index, value) in map.iter()
+    /// {
+    ///     println!("Index: {:?}, Value: {}", index, value);
+    /// }
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T, K>
+    {
+        Iter
+        {
+            internal: self.items.iter(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Returns an iterator of mutable references to the map's values in no particular order
+    ///
+    /// Each iterator step returns (Index, &mut T)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::monotonic();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(allocator.allocate(), 0);
+    /// map.set(allocator.allocate(), 1);
+    ///
+    /// for (_, value) in map.iter_mut()
+    /// {
+    ///     *value += 10;
+    /// }
+    /// assert_eq!(map.iter().map(|(_, value)| *value).sum::<i32>(), 21);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, K>
+    {
+        IterMut
+        {
+            internal: self.items.iter_mut(),
+            key_type: PhantomData
+        }
+    }
+
+    /// Consumes the map, converting every value with `f` into a map of `U`
+    ///
+    /// Every value keeps its slot and generation, so indices into this map stay valid in the new one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::Index;
+    /// use gen_vec::exposed::{IndexAllocator, SparseGenMap};
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    ///
+    /// let mut map: SparseGenMap<i32> = SparseGenMap::new();
+    /// map.set(index, 2);
+    ///
+    /// let names: SparseGenMap<String> = map.map(|_, value| format!("#{}", value));
+    /// assert_eq!(names[index], "#2");
+    /// ```
+    pub fn map<U, F>(self, mut f: F) -> SparseGenMap<U, K>
+        where F: FnMut(K, T) -> U
+    {
+        let items = self.items
+                        .into_iter()
+                        .map(|(slot, Item { value, generation })|
+                            (slot, Item { value: f(K::from_raw_parts(slot, generation), value), generation }))
+                        .collect();
+        SparseGenMap
+        {
+            items,
+            removed: self.removed,
+            key_type: PhantomData
+        }
+    }
+
+    /// Generation the value at `slot` has, or had before it was removed
+    fn generation(&self, slot: usize) -> Option<usize>
+    {
+        self.items.get(&slot).map(|item| item.generation).or_else(| | self.removed.get(&slot).copied())
+    }
+}
+
+/// Why `index` finds no value, given the generations of removed values
+fn vacant(removed: &HashMap<usize, usize>, index: Index) -> IndexError
+{
+    match removed.get(&index.index)
+    {
+        Some(&generation) if generation > index.generation => IndexError::Stale { current_generation: generation },
+        _ => IndexError::Vacant
+    }
+}
+
+/// Struct for consuming a `SparseGenMap` into an iterator
+#[derive(Debug)]
+pub struct IntoIter<T, K = Index>
+{
+    internal: hash_map::IntoIter<usize, Item<T>>,
+    key_type: PhantomData<K>
+}
+
+impl<T, K: Key> Iterator for IntoIter<T, K>
+{
+    type Item = (K, T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        self.internal.next().map(|(index, item)| (K::from_raw_parts(index, item.generation), item.value))
+    }
+}
+
+impl<T, K: Key> IntoIterator for SparseGenMap<T, K>
+{
+    type Item = (K, T);
+    type IntoIter = IntoIter<T, K>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        IntoIter
+        {
+            internal: self.items.into_iter(),
+            key_type: PhantomData
+        }
+    }
+}
+
+/// Struct for creating an iterator over an immutable `SparseGenMap` reference
+#[derive(Debug)]
+pub struct Iter<'a, T: 'a, K = Index>
+{
+    internal: hash_map::Iter<'a, usize, Item<T>>,
+    key_type: PhantomData<K>
+}
+
+impl<'a, T, K: Key> Iterator for Iter<'a, T, K>
+{
+    type Item = (K, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        self.internal.next().map(|(index, item)| (K::from_raw_parts(*index, item.generation), &item.value))
+    }
+}
+
+impl<'a, T, K: Key> IntoIterator for &'a SparseGenMap<T, K>
+{
+    type Item = (K, &'a T);
+    type IntoIter = Iter<'a, T, K>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+/// Struct for creating an iterator over a mutable `SparseGenMap` reference
+#[derive(Debug)]
+pub struct IterMut<'a, T: 'a, K = Index>
+{
+    internal: hash_map::IterMut<'a, usize, Item<T>>,
+    key_type: PhantomData<K>
+}
+
+impl<'a, T: 'a, K: Key> Iterator for IterMut<'a, T, K>
+{
+    type Item = (K, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        self.internal.next().map(|(index, item)| (K::from_raw_parts(*index, item.generation), &mut item.value))
+    }
+}
+
+impl<'a, T, K: Key> IntoIterator for &'a mut SparseGenMap<T, K>
+{
+    type Item = (K, &'a mut T);
+    type IntoIter = IterMut<'a, T, K>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter_mut()
+    }
+}
+
+impl<T, K: Key> std::ops::Index<K> for SparseGenMap<T, K>
+{
+    type Output = T;
+
+    fn index(&self, index: K) -> &Self::Output
+    {
+        self.try_get(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
+    }
+}
+
+impl<T, K: Key> std::ops::IndexMut<K> for SparseGenMap<T, K>
+{
+    fn index_mut(&mut self, index: K) -> &mut Self::Output
+    {
+        self.try_get_mut(index).unwrap_or_else(|error| panic!("Index should be valid: {:?}, {}", index, error))
+    }
+}
+
+#[cfg(test)]
+mod map_tests
+{
+    use crate::exposed::*;
+    use crate::{Index, IndexError, TransferError};
+
+    #[test]
+    fn set()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        assert_eq!(map.set(index, 0), None);
+        assert!(map.contains(index));
+
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+
+        assert_eq!(map.set(index1, 1), Some(0));
+        assert!(!map.contains(index));
+        assert!(map.contains(index1));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn remove()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        map.set(index, 0);
+
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+        assert_eq!(map.remove(index1), None);
+        assert_eq!(map.remove(index), Some(0));
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn stale_set_after_remove()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        map.set(index1, 1);
+        assert!(map.try_set(index, 0).is_err());
+
+        // Removing the newer value keeps its generation, like a vacant slot of an `ExposedGenVec`
+        map.remove(index1);
+        assert_eq!(map.try_set(index, 0).map_err(|error| error.current_generation()), Err(1));
+        assert_eq!(map.try_get(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(map.try_get(index1), Err(IndexError::Vacant));
+        assert!(map.is_empty());
+
+        let mut other = SparseGenMap::new();
+        other.set(index, 2);
+        assert_eq!(other.transfer(&mut map, index), Err(TransferError::DestinationNewer { current_generation: 1 }));
+
+        // Setting the slot again replaces the removed generation
+        assert_eq!(map.try_set(index1, 1), Ok(None));
+        assert_eq!(map.get(index1), Some(&1));
+    }
+
+    #[test]
+    fn drop_removed_generations()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+        let index1 = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        map.set(index, 0);
+        map.set(index1, 1);
+        map.remove(index);
+        map.remove(index1);
+
+        // Removed generations are kept while their indices are active
+        allocator.deallocate(index);
+        assert_eq!(map.retain_live(&allocator), 0);
+        assert_eq!(map.removed.len(), 1);
+        assert!(map.try_set(index1, 2).is_ok());
+        assert!(map.removed.is_empty());
+    }
+
+    #[test]
+    fn only_stores_values()
+    {
+        let mut allocator = IndexAllocator::monotonic();
+        let subscription = allocator.subscribe();
+        let mut map = SparseGenMap::new();
+
+        for value in 0..1000
+        {
+            let index = allocator.allocate();
+            map.set(index, value);
+            if value != 999
+            {
+                allocator.deallocate(index);
+                map.remove(index);
+            }
+        }
+
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.iter().collect::<Vec<(Index, &i32)>>(), vec![(Index { index: 999, generation: 0 }, &999)]);
+
+        // Removed generations stay until the allocator reports their slots freed
+        assert_eq!(map.removed.len(), 999);
+        assert_eq!(map.purge_removed(allocator.drain_removed(subscription)), 0);
+        assert!(map.removed.is_empty());
+    }
+
+    #[test]
+    fn try_get()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        map.set(index, 0);
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+
+        assert_eq!(map.try_get(index1), Err(IndexError::FromFuture));
+        map.set(index1, 1);
+        assert_eq!(map.try_get_mut(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(map.try_remove(index), Err(IndexError::Stale { current_generation: 1 }));
+        assert_eq!(map[index1], 1);
+    }
+
+    #[test]
+    fn transfer()
+    {
+        let mut allocator = IndexAllocator::new();
+        let index = allocator.allocate();
+
+        let mut from = SparseGenMap::new();
+        let mut to = SparseGenMap::new();
+        from.set(index, 0);
+        allocator.deallocate(index);
+        let index1 = allocator.allocate();
+        to.set(index1, 1);
+
+        assert_eq!(from.transfer(&mut to, index), Err(TransferError::DestinationNewer { current_generation: 1 }));
+        assert_eq!(from.transfer(&mut to, index1), Err(TransferError::Source(IndexError::FromFuture)));
+        assert_eq!(from.len(), 1);
+        assert_eq!(to.into_iter().collect::<Vec<(Index, i32)>>(), vec![(index1, 1)]);
+    }
+
+    #[test]
+    fn swap_and_map()
+    {
+        let mut allocator = IndexAllocator::new();
+        let a = allocator.allocate();
+        let b = allocator.allocate();
+
+        let mut map = SparseGenMap::new();
+        map.set(a, 0);
+        assert_eq!(map.swap(a, b), Err(IndexError::Vacant));
+        map.set(b, 1);
+        assert_eq!(map.swap(a, b), Ok(()));
+        assert_eq!(map.swap(a, a), Ok(()));
+
+        let map = map.map(|index, value| (index, value));
+        assert_eq!(map[a], (a, 1));
+        assert_eq!(map[b], (b, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "index points to a vacant slot")]
+    fn index_vacant()
+    {
+        let mut allocator = IndexAllocator::new();
+        let map = SparseGenMap::<i32>::new();
+        let _ = map[allocator.allocate()];
+    }
+}
@@ -0,0 +1,247 @@
+use std::{fmt, hash::Hash};
+use crate::Index;
+
+/// A generational index that can be used to access the values of generational vecs
+///
+/// `Index` is the default key. Declare distinct key types with `new_key_type!` so
+/// indices of unrelated vecs can't be mixed up
+pub trait Key: Copy + Eq + Ord + Hash + fmt::Debug
+{
+    /// Returns a key pointing to slot `index` at `generation`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, Key};
+    ///
+    /// let index: Index = Index::from_raw_parts(3, 1);
+    /// assert_eq!(index.into_raw_parts(), (3, 1));
+    /// ```
+    fn from_raw_parts(index: usize, generation: usize) -> Self;
+
+    /// Returns the slot and generation of the key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gen_vec::{Index, Key};
+    /// use gen_vec::exposed::IndexAllocator;
+    ///
+    /// let mut allocator: IndexAllocator = IndexAllocator::new();
+    /// let index: Index = allocator.allocate();
+    /// assert_eq!(index.into_raw_parts(), (0, 0));
+    /// ```
+    fn into_raw_parts(self) -> (usize, usize);
+}
+
+impl Key for Index
+{
+    #[inline]
+    fn from_raw_parts(index: usize, generation: usize) -> Index
+    {
+        Index { index, generation }
+    }
+
+    #[inline]
+    fn into_raw_parts(self) -> (usize, usize)
+    {
+        (self.index, self.generation)
+    }
+}
+
+impl From<(usize, usize)> for Index
+{
+    #[inline]
+    fn from((index, generation): (usize, usize)) -> Index
+    {
+        Index { index, generation }
+    }
+}
+
+impl From<Index> for (usize, usize)
+{
+    #[inline]
+    fn from(index: Index) -> (usize, usize)
+    {
+        (index.index, index.generation)
+    }
+}
+
+impl Index
+{
+    /// Converts any key into an `Index`
+    pub(crate) fn from_key<K: Key>(key: K) -> Index
+    {
+        let (index, generation) = key.into_raw_parts();
+        Index { index, generation }
+    }
+
+    /// Converts the `Index` into any key
+    pub(crate) fn into_key<K: Key>(self) -> K
+    {
+        K::from_raw_parts(self.index, self.generation)
+    }
+}
+
+/// Declares new key types that can be used in place of `Index`
+///
+/// Each key type wraps an `Index` but is a distinct type, so keys of one vec can't be used with another.
+/// Key types convert to and from their raw `(index, generation)` parts and serialize the same
+/// way `Index` does when the `serde` feature is enabled
+///
+/// # Examples
+///
+/// ```
+/// use gen_vec::{new_key_type, Key};
+/// use gen_vec::closed::ClosedGenVec;
+///
+/// new_key_type!
+/// {
+///     /// Handle to an entity
+///     pub struct EntityId;
+///     struct BodyId;
+/// }
+///
+/// let mut entities: ClosedGenVec<&str, EntityId> = ClosedGenVec::with_key();
+/// let id: EntityId = entities.insert("player");
+/// assert_eq!(entities.get(id), Some(&"player"));
+///
+/// let (index, generation): (usize, usize) = id.into();
+/// assert_eq!(EntityId::from((index, generation)), id);
+/// ```
+#[macro_export]
+macro_rules! new_key_type
+{
+    ( $( $(#[$outer:meta])* $vis:vis struct $name:ident; )* ) =>
+    {
+        $(
+            $(#[$outer])*
+            #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
+            $vis struct $name($crate::Index);
+
+            impl $crate::Key for $name
+            {
+                fn from_raw_parts(index: usize, generation: usize) -> $name
+                {
+                    $name(<$crate::Index as $crate::Key>::from_raw_parts(index, generation))
+                }
+
+                fn into_raw_parts(self) -> (usize, usize)
+                {
+                    $crate::Key::into_raw_parts(self.0)
+                }
+            }
+
+            impl ::std::convert::From<(usize, usize)> for $name
+            {
+                fn from((index, generation): (usize, usize)) -> $name
+                {
+                    <$name as $crate::Key>::from_raw_parts(index, generation)
+                }
+            }
+
+            impl ::std::convert::From<$name> for (usize, usize)
+            {
+                fn from(key: $name) -> (usize, usize)
+                {
+                    $crate::Key::into_raw_parts(key)
+                }
+            }
+
+            $crate::__serialize_key!($name);
+        )*
+    };
+}
+
+/// Implements `Serialize` and `Deserialize` for a key declared with `new_key_type!`
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serialize_key
+{
+    ($name:ident) =>
+    {
+        impl $crate::__serde::Serialize for $name
+        {
+            fn serialize<S: $crate::__serde::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            {
+                $crate::__serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> $crate::__serde::Deserialize<'de> for $name
+        {
+            fn deserialize<D: $crate::__serde::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<$name, D::Error>
+            {
+                <$crate::Index as $crate::__serde::Deserialize<'de>>::deserialize(deserializer).map($name)
+            }
+        }
+    };
+}
+
+/// Serialization is only implemented with the `serde` feature
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __serialize_key
+{
+    ($name:ident) => {};
+}
+
+#[cfg(test)]
+mod key_tests
+{
+    use crate::{Index, Key};
+    use crate::closed::ClosedGenVec;
+    use crate::exposed::{IndexAllocator, ExposedGenVec};
+
+    new_key_type!
+    {
+        struct EntityId;
+        pub(crate) struct BodyId;
+    }
+
+    #[test]
+    fn raw_parts()
+    {
+        let id = EntityId::from_raw_parts(4, 2);
+        assert_eq!(id.into_raw_parts(), (4, 2));
+        assert_eq!(EntityId::from((4, 2)), id);
+        assert_eq!(<(usize, usize)>::from(id), (4, 2));
+        assert_eq!(Index::from((4, 2)), Index { index: 4, generation: 2 });
+        assert_eq!(format!("{:?}", id), "EntityId(Index { index: 4, generation: 2 })");
+    }
+
+    #[test]
+    fn custom_keys()
+    {
+        let mut allocator: IndexAllocator<BodyId> = IndexAllocator::with_key();
+        let body = allocator.allocate();
+
+        let mut bodies: ExposedGenVec<f32, BodyId> = ExposedGenVec::with_key();
+        bodies.set(body, 1.5);
+        assert_eq!(bodies.get(body), Some(&1.5));
+
+        let mut entities: ClosedGenVec<&str, EntityId> = ClosedGenVec::with_key();
+        let entity = entities.insert("player");
+        assert_eq!(entities[entity], "player");
+        assert_eq!(entities.iter().map(|(id, _)| id).collect::<Vec<EntityId>>(), vec![entity]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize()
+    {
+        use serde::{Serialize, Deserialize};
+
+        #[derive(Serialize, Deserialize)]
+        struct Bodies
+        {
+            ids: Vec<BodyId>
+        }
+
+        fn assert_serde<'de, T: Serialize + Deserialize<'de>>() {}
+        assert_serde::<Bodies>();
+        assert_serde::<ExposedGenVec<f32, BodyId>>();
+    }
+}
@@ -25,10 +25,20 @@
 //!
 //! Every time an `Index` is reused, the internal generation is incremented. This ensures that a deallocated
 //! `Index` handle can't access data that it no longer validly points to
+//!
+//! ## Custom key types
+//!
+//! Every vec and `IndexAllocator` is generic over a `Key`, which defaults to `Index`. Use `new_key_type!`
+//! to declare distinct key types so an `EntityId` can't be used to index a vec of bodies, then construct
+//! vecs with `with_key` instead of `new`
 
 #[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
 
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use ::serde as __serde;
+
 /// An index of a generational vec
 #[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Clone, Copy, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -47,5 +57,14 @@ struct Item<T>
     generation: usize
 }
 
+mod key;
+pub use self::key::*;
+mod error;
+pub use self::error::*;
+mod stats;
+pub use self::stats::*;
+mod bitset;
+pub use self::bitset::*;
+
 pub mod closed;
 pub mod exposed;
\ No newline at end of file
@@ -0,0 +1,31 @@
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+
+/// Snapshot of the health of an `IndexAllocator` or generational vec
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats
+{
+    /// Total number of indices ever allocated
+    pub total_allocations: usize,
+    /// Total number of indices ever freed
+    pub total_frees: usize,
+    /// Highest number of indices that were active at once
+    pub peak_active: usize,
+    /// Highest generation of any slot
+    pub max_generation: usize,
+    /// Slots whose generations have been exhausted and will never be reused
+    pub retired_slots: usize,
+    /// Number of free slots waiting to be reused
+    pub free_list_len: usize,
+    /// Number of freed slots waiting out their quarantine before they can be reused
+    pub quarantined: usize,
+    /// Active slots divided by the total number of slots. `1.0` when there are no slots
+    pub fragmentation: f64,
+    /// Approximate heap bytes used by stored values. `0` for a bare `IndexAllocator`
+    pub items_bytes: usize,
+    /// Approximate heap bytes used to track each slot's state
+    pub active_indices_bytes: usize,
+    /// Approximate heap bytes used by the free list and quarantine
+    pub free_indices_bytes: usize
+}